@@ -5,7 +5,10 @@ const screenIds = [
     "authorizeScreen",
     "noActionsScreen",
     "sendMessageScreen",
+    "markerScreen",
     "adBreakScreen",
+    "propertiesScreen",
+    "socialsScreen",
 ];
 
 function setActiveScreen(id) {
@@ -95,6 +98,685 @@ tilepad.tile.onProperties((properties) => {
     }
 });
 
+// === Action Properties Screen ===
+
+const usernameField = {
+    name: "username",
+    label: "Username",
+    type: "text",
+    placeholder: "username",
+    description: "Login of the user",
+};
+
+const targetLoginField = {
+    name: "target_login",
+    label: "Channel",
+    type: "text",
+    placeholder: "channel",
+    description: "Login of the channel",
+};
+
+const toggleFields = [
+    {
+        name: "channels",
+        label: "Mirrored channels",
+        type: "list",
+        description:
+            "Other channels the setting is also applied to, one per line",
+    },
+];
+
+const announcementColorOptions = [
+    ["blue", "Blue"],
+    ["green", "Green"],
+    ["orange", "Orange"],
+    ["purple", "Purple"],
+];
+
+const chatModeOptions = [
+    ["EMOTE_ONLY", "Emote only"],
+    ["SUB_ONLY", "Subscriber only"],
+    ["FOLLOWER_ONLY", "Follower only"],
+    ["SLOW_MODE", "Slow mode"],
+];
+
+// Editable properties for actions without a dedicated screen
+const actionFields = {
+    emote_only: toggleFields,
+    follower_only: toggleFields,
+    sub_only: toggleFields,
+    slow_mode: toggleFields,
+    go_live: [
+        { name: "title", label: "Title", type: "text" },
+        { name: "category", label: "Category", type: "text" },
+        {
+            name: "announcement",
+            label: "Announcement",
+            type: "text",
+            description: "Only the steps with a value are performed",
+        },
+    ],
+    capture_moment: [
+        {
+            name: "label",
+            label: "Label",
+            type: "text",
+            description: "Label shared by the clip, marker and log entry",
+        },
+    ],
+    toggle_chat_color: [
+        {
+            name: "first",
+            label: "First color",
+            type: "text",
+            placeholder: "blue_violet",
+        },
+        {
+            name: "second",
+            label: "Second color",
+            type: "text",
+            placeholder: "#9146FF",
+            description: "Named twitch color or hex color",
+        },
+    ],
+    lurk_mode: [
+        {
+            name: "message",
+            label: "Message",
+            type: "text",
+            placeholder: "Thanks for lurking!",
+        },
+    ],
+    clip_and_post: [
+        {
+            name: "delay",
+            label: "Delay",
+            type: "number",
+            placeholder: "5",
+            description: "Seconds to wait before checking if the clip is ready",
+        },
+        {
+            name: "message",
+            label: "Message",
+            type: "text",
+            placeholder: "Check out this clip! {url}",
+        },
+    ],
+    set_dated_title: [
+        {
+            name: "template",
+            label: "Title",
+            type: "text",
+            placeholder: "Stream {date}",
+            description: "{date} is replaced with the current date",
+        },
+        {
+            name: "date_format",
+            label: "Date format",
+            type: "text",
+            placeholder: "YYYY-MM-DD",
+        },
+    ],
+    fulfill_redemptions: [
+        { name: "reward_id", label: "Reward", type: "reward" },
+        {
+            name: "status",
+            label: "Status",
+            type: "select",
+            options: [
+                ["FULFILLED", "Fulfilled"],
+                ["CANCELED", "Canceled"],
+            ],
+        },
+        {
+            name: "count",
+            label: "Count",
+            type: "number",
+            description:
+                "Number of redemptions to update, oldest first (max 50)",
+        },
+    ],
+    marker_category: [
+        {
+            name: "labels",
+            label: "Labels",
+            type: "list",
+            description: "Labels cycled through on each press, one per line",
+        },
+    ],
+    post_standings: [
+        {
+            name: "template",
+            label: "Message",
+            type: "text",
+            placeholder: "{title}: {standings}",
+        },
+    ],
+    announce_goal: [
+        {
+            name: "template",
+            label: "Announcement",
+            type: "text",
+            placeholder: "We're {percent}% to {target} {unit}!",
+        },
+    ],
+    raid_slow_mode: [
+        {
+            name: "wait_time",
+            label: "Wait time",
+            type: "number",
+            description: "3-120 seconds",
+        },
+        {
+            name: "duration_minutes",
+            label: "Duration (minutes)",
+            type: "number",
+        },
+    ],
+    cycle_announcement: [
+        {
+            name: "announcements",
+            label: "Announcements",
+            type: "announcements",
+            placeholder: "purple: Welcome to the stream!",
+            description:
+                "Posted in order, one per press. Prefix a line with a color (e.g purple:) to change its color",
+        },
+    ],
+    sub_hype: [
+        { name: "message", label: "Announcement", type: "text" },
+        {
+            name: "mode",
+            label: "Chat mode",
+            type: "select",
+            options: chatModeOptions,
+        },
+        {
+            name: "duration",
+            label: "Duration",
+            type: "number",
+            description: "Seconds before the chat mode is restored",
+        },
+    ],
+    toggle_reward_auto_fulfill: [
+        { name: "reward_id", label: "Reward", type: "reward" },
+    ],
+    post_chat_status: [
+        {
+            name: "template",
+            label: "Message",
+            type: "text",
+            placeholder: "Chat is in {restrictions}",
+        },
+        {
+            name: "post_when_clear",
+            label: "Post when no restrictions are active",
+            type: "checkbox",
+        },
+    ],
+    post_raid_thanks: [
+        {
+            name: "template",
+            label: "Message",
+            type: "text",
+            description: "Supports {raider} and {viewers}",
+        },
+    ],
+    export_markers: [
+        {
+            name: "path",
+            label: "Path",
+            type: "text",
+            description: "File to write the chapters to",
+        },
+        {
+            name: "format",
+            label: "Format",
+            type: "select",
+            options: [
+                ["youtube", "YouTube"],
+                ["webvtt", "WebVTT"],
+            ],
+        },
+    ],
+    cycle_chat_delay: [
+        {
+            name: "delays",
+            label: "Delays",
+            type: "numberList",
+            placeholder: "0, 2, 4, 6",
+            description:
+                "Seconds cycled through on each press, 0 disables the delay",
+        },
+    ],
+    marked_message: [
+        {
+            name: "message",
+            label: "Message",
+            type: "text",
+            description: "Posted to chat and used as the marker description",
+        },
+    ],
+    clear_chat_with_warning: [
+        {
+            name: "message",
+            label: "Warning",
+            type: "text",
+            placeholder: "Chat is being cleared in {seconds} seconds",
+        },
+        {
+            name: "delay",
+            label: "Delay",
+            type: "number",
+            description: "0-30 seconds",
+        },
+    ],
+    post_random_clip: [
+        {
+            name: "template",
+            label: "Message",
+            type: "text",
+            placeholder: "Check out this clip! {url}",
+            description: "Supports {url}, {title} and {creator}",
+        },
+    ],
+    focus_mode: [
+        { name: "enter_message", label: "Enter message", type: "text" },
+        { name: "exit_message", label: "Exit message", type: "text" },
+    ],
+    temp_vip: [
+        targetLoginField,
+        {
+            name: "minutes",
+            label: "Minutes",
+            type: "number",
+            description: "Time the chatter remains a VIP",
+        },
+    ],
+    post_next_stream: [
+        {
+            name: "template",
+            label: "Message",
+            type: "text",
+            placeholder: "Next stream: {title} in {time}",
+        },
+    ],
+    timeout_user: [
+        usernameField,
+        {
+            name: "duration",
+            label: "Duration",
+            type: "number",
+            description: "Seconds (max 1209600)",
+        },
+        { name: "reason", label: "Reason", type: "text" },
+    ],
+    ban_user: [
+        usernameField,
+        { name: "reason", label: "Reason", type: "text" },
+    ],
+    announcement: [
+        { name: "message", label: "Message", type: "text" },
+        {
+            name: "color",
+            label: "Color",
+            type: "select",
+            options: announcementColorOptions,
+            emptyLabel: "Primary",
+        },
+    ],
+    start_raid: [targetLoginField],
+    shoutout: [targetLoginField],
+    update_category: [{ name: "game_name", label: "Category", type: "text" }],
+    create_poll: [
+        { name: "title", label: "Title", type: "text" },
+        {
+            name: "choices",
+            label: "Choices",
+            type: "list",
+            description: "2-5 choices, one per line",
+        },
+        {
+            name: "duration",
+            label: "Duration",
+            type: "number",
+            description: "15-1800 seconds",
+        },
+    ],
+    end_poll: [
+        {
+            name: "status",
+            label: "Ending",
+            type: "select",
+            options: [
+                ["TERMINATED", "End and show results"],
+                ["ARCHIVED", "End and hide results"],
+            ],
+        },
+    ],
+    create_prediction: [
+        { name: "title", label: "Title", type: "text" },
+        {
+            name: "outcomes",
+            label: "Outcomes",
+            type: "list",
+            description: "2-10 outcomes, one per line",
+        },
+        {
+            name: "window",
+            label: "Window",
+            type: "number",
+            description: "30-1800 seconds",
+        },
+    ],
+    resolve_prediction: [
+        {
+            name: "status",
+            label: "Status",
+            type: "select",
+            options: [
+                ["LOCKED", "Lock"],
+                ["RESOLVED", "Resolve"],
+                ["CANCELED", "Cancel"],
+            ],
+            emptyLabel: "Select...",
+        },
+        {
+            name: "winning_outcome_index",
+            label: "Winning outcome",
+            type: "number",
+            description:
+                "Position of the winning outcome starting at 0, required when resolving",
+        },
+    ],
+    add_vip: [usernameField],
+    remove_vip: [usernameField],
+    add_moderator: [usernameField],
+    remove_moderator: [usernameField],
+    warn_user: [
+        usernameField,
+        {
+            name: "reason",
+            label: "Reason",
+            type: "text",
+            description: "Shown to the user",
+        },
+    ],
+    delete_message: [{ name: "message_id", label: "Message ID", type: "text" }],
+    block_user: [usernameField],
+    unblock_user: [usernameField],
+    set_slow_mode: [
+        {
+            name: "enabled",
+            label: "Enable slow mode",
+            type: "checkbox",
+            default: true,
+        },
+        {
+            name: "wait_seconds",
+            label: "Wait time",
+            type: "number",
+            description:
+                "3-120 seconds, the current wait time is kept when empty",
+        },
+    ],
+    set_follower_mode: [
+        {
+            name: "enabled",
+            label: "Enable follower only mode",
+            type: "checkbox",
+            default: true,
+        },
+        {
+            name: "duration_minutes",
+            label: "Follow duration (minutes)",
+            type: "number",
+            description: "The current duration is kept when empty",
+        },
+    ],
+};
+
+const propertiesForm = document.getElementById("propertiesForm");
+
+// Inputs for the fields of the current action
+const fieldInputs = [];
+
+// Converts the input value into the property value
+function readField(field, inputEl) {
+    const value = inputEl.value.trim();
+
+    switch (field.type) {
+        case "checkbox":
+            return inputEl.checked;
+        case "number":
+            return value === "" ? null : Number(value);
+        case "list":
+            return value
+                .split("\n")
+                .map((line) => line.trim())
+                .filter((line) => line !== "");
+        case "numberList":
+            return value
+                .split(",")
+                .map((item) => item.trim())
+                .filter((item) => item !== "")
+                .map(Number);
+        case "announcements":
+            return value
+                .split("\n")
+                .map((line) => line.trim())
+                .filter((line) => line !== "")
+                .map((line) => {
+                    const [color, ...rest] = line.split(":");
+                    const isColor = announcementColorOptions.some(
+                        ([option]) => option === color.trim().toLowerCase(),
+                    );
+
+                    if (rest.length === 0 || !isColor) return { message: line };
+                    return {
+                        message: rest.join(":").trim(),
+                        color: color.trim().toLowerCase(),
+                    };
+                });
+        default:
+            return value === "" ? null : value;
+    }
+}
+
+// Shows the property value within the input
+function writeField(field, inputEl, value) {
+    switch (field.type) {
+        case "checkbox":
+            inputEl.checked = value ?? field.default ?? false;
+            break;
+        case "list":
+            inputEl.value = (value ?? []).join("\n");
+            break;
+        case "numberList":
+            inputEl.value = (value ?? []).join(", ");
+            break;
+        case "announcements":
+            inputEl.value = (value ?? [])
+                .map((entry) =>
+                    entry.color
+                        ? `${entry.color}: ${entry.message}`
+                        : entry.message,
+                )
+                .join("\n");
+            break;
+        case "reward":
+            // Rewards may not have loaded yet, kept to select once loaded
+            inputEl.dataset.value = value ?? "";
+            inputEl.value = value ?? "";
+            break;
+        default:
+            inputEl.value = value ?? "";
+            break;
+    }
+}
+
+function setSelectOptions(selectEl, options, emptyLabel) {
+    selectEl.replaceChildren();
+
+    for (const [value, label] of [["", emptyLabel], ...options]) {
+        const optionEl = document.createElement("option");
+        optionEl.value = value;
+        optionEl.innerText = label;
+        selectEl.appendChild(optionEl);
+    }
+}
+
+function createField(field) {
+    const id = `field-${field.name}`;
+
+    const itemEl = document.createElement("div");
+    itemEl.className = "tile-item";
+
+    const labelEl = document.createElement("label");
+    labelEl.className = "tile-label";
+    labelEl.htmlFor = id;
+    labelEl.innerText = field.label;
+    itemEl.appendChild(labelEl);
+
+    let inputEl;
+    switch (field.type) {
+        case "select":
+        case "reward": {
+            inputEl = document.createElement("select");
+            inputEl.className = "tile-select";
+            setSelectOptions(
+                inputEl,
+                field.options ?? [],
+                field.emptyLabel ?? "Default",
+            );
+            break;
+        }
+        case "list":
+        case "announcements": {
+            inputEl = document.createElement("textarea");
+            inputEl.className = "tile-input";
+            inputEl.rows = 4;
+            break;
+        }
+        default: {
+            inputEl = document.createElement("input");
+            inputEl.className = "tile-input";
+            inputEl.type = field.type === "numberList" ? "text" : field.type;
+            break;
+        }
+    }
+
+    inputEl.id = id;
+    if (field.placeholder) inputEl.placeholder = field.placeholder;
+    inputEl.setAttribute("disabled", "");
+
+    inputEl.onchange = () => {
+        tilepad.tile.setProperty(field.name, readField(field, inputEl));
+    };
+
+    itemEl.appendChild(inputEl);
+
+    if (field.description) {
+        const descriptionEl = document.createElement("p");
+        descriptionEl.className = "tile-description";
+        descriptionEl.innerText = field.description;
+        itemEl.appendChild(descriptionEl);
+    }
+
+    fieldInputs.push({ field, inputEl });
+    return itemEl;
+}
+
+function renderPropertiesForm(fields) {
+    propertiesForm.replaceChildren(...fields.map(createField));
+
+    // Reward options are provided by the plugin
+    if (fields.some((field) => field.type === "reward")) {
+        tilepad.plugin.send({ type: "GET_REWARDS" });
+    }
+}
+
+function setRewards(rewards) {
+    const options = rewards.map((reward) => [reward.id, reward.title]);
+
+    for (const { field, inputEl } of fieldInputs) {
+        if (field.type !== "reward") continue;
+
+        setSelectOptions(inputEl, options, "Select a reward...");
+        inputEl.value = inputEl.dataset.value ?? "";
+    }
+}
+
+tilepad.tile.onProperties((properties) => {
+    if (!actionFields[currentAction]) return;
+
+    for (const { field, inputEl } of fieldInputs) {
+        writeField(field, inputEl, properties[field.name]);
+        inputEl.removeAttribute("disabled");
+    }
+});
+
+// === Socials Screen ===
+
+const socialsList = document.getElementById("socialsList");
+const addSocialBtn = document.getElementById("addSocial");
+
+let socials = [];
+
+function saveSocials() {
+    tilepad.plugin.send({
+        type: "SET_SOCIALS",
+        socials: socials.filter(
+            (social) => social.name !== "" && social.url !== "",
+        ),
+    });
+}
+
+function createSocialInput(social, key, placeholder) {
+    const inputEl = document.createElement("input");
+    inputEl.type = "text";
+    inputEl.className = "tile-input";
+    inputEl.placeholder = placeholder;
+    inputEl.value = social[key];
+
+    inputEl.onchange = (event) => {
+        social[key] = event.target.value.trim();
+        saveSocials();
+    };
+
+    return inputEl;
+}
+
+function renderSocials() {
+    socialsList.replaceChildren(
+        ...socials.map((social, index) => {
+            const rowEl = document.createElement("div");
+            rowEl.className = "social-row";
+
+            const removeBtn = document.createElement("button");
+            removeBtn.className = "tile-button";
+            removeBtn.innerText = "Remove";
+            removeBtn.onclick = () => {
+                socials.splice(index, 1);
+                renderSocials();
+                saveSocials();
+            };
+
+            rowEl.append(
+                createSocialInput(social, "name", "Discord"),
+                createSocialInput(social, "url", "https://discord.gg/example"),
+                removeBtn,
+            );
+            return rowEl;
+        }),
+    );
+}
+
+addSocialBtn.onclick = () => {
+    socials.push({ name: "", url: "" });
+    renderSocials();
+};
+
 // === Logout Button ===
 
 const logoutBtn = document.getElementById("logout");
@@ -137,9 +819,19 @@ tilepad.plugin.onMessage((data) => {
                             break;
                         }
 
-                        // Default page for no additional options
+                        case "post_socials": {
+                            setActiveScreen("socialsScreen");
+                            tilepad.plugin.send({ type: "GET_SOCIALS" });
+                            break;
+                        }
+
+                        // Generic form for actions with properties
                         default: {
-                            setActiveScreen("noActionsScreen");
+                            if (actionFields[currentAction]) {
+                                setActiveScreen("propertiesScreen");
+                            } else {
+                                setActiveScreen("noActionsScreen");
+                            }
                             break;
                         }
                     }
@@ -150,6 +842,17 @@ tilepad.plugin.onMessage((data) => {
 
             break;
         }
+
+        case "SOCIALS": {
+            socials = data.socials.map((social) => ({ ...social }));
+            renderSocials();
+            break;
+        }
+
+        case "REWARDS": {
+            setRewards(data.rewards);
+            break;
+        }
     }
 });
 
@@ -160,6 +863,9 @@ tilepad.tile
     .then((tile) => {
         currentAction = tile.actionId;
 
+        const fields = actionFields[currentAction];
+        if (fields) renderPropertiesForm(fields);
+
         // Request connection state from the plugin
         tilepad.plugin.send({
             type: "GET_STATE",
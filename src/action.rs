@@ -1,37 +1,335 @@
-use serde::Deserialize;
-use twitch_api::types::CommercialLength;
+use serde::{Deserialize, Deserializer, Serialize, de::Error as _};
+use twitch_api::{
+    helix::{
+        Scope,
+        chat::{ChatSettings, UpdateChatSettingsBody},
+        points::CustomRewardRedemptionStatus,
+    },
+    types::CommercialLength,
+};
+
+use crate::{error::TwitchError, marker_log::ChapterFormat};
+
+/// Scopes required by each action, keyed by action ID. Every action must
+/// be listed here so its scopes are requested when authenticating, actions
+/// that are not listed are rejected by [Action::from_action]
+pub const ACTION_SCOPES: &[(&str, &[Scope])] = &[
+    ("send_message", &[Scope::UserWriteChat]),
+    ("clear_chat", &[Scope::ModeratorManageChatMessages]),
+    ("emote_only", &[Scope::ModeratorManageChatSettings]),
+    ("follower_only", &[Scope::ModeratorManageChatSettings]),
+    ("sub_only", &[Scope::ModeratorManageChatSettings]),
+    ("slow_mode", &[Scope::ModeratorManageChatSettings]),
+    ("ad_break", &[Scope::ChannelEditCommercial]),
+    ("marker", &[Scope::ChannelManageBroadcast]),
+    ("create_clip", &[Scope::ClipsEdit]),
+    ("open_clip", &[Scope::ClipsEdit]),
+    ("viewer_count", &[]),
+    (
+        "go_live",
+        &[
+            Scope::ChannelManageBroadcast,
+            Scope::ModeratorManageAnnouncements,
+        ],
+    ),
+    (
+        "capture_moment",
+        &[Scope::ClipsEdit, Scope::ChannelManageBroadcast],
+    ),
+    ("toggle_chat_color", &[Scope::UserManageChatColor]),
+    ("post_category_rank", &[Scope::UserWriteChat]),
+    ("clip_and_edit", &[Scope::ClipsEdit]),
+    ("toggle_mature", &[Scope::ChannelManageBroadcast]),
+    ("post_followers_gained", &[Scope::UserWriteChat]),
+    ("post_socials", &[Scope::UserWriteChat]),
+    ("lurk_mode", &[Scope::UserWriteChat]),
+    ("clip_and_post", &[Scope::ClipsEdit, Scope::UserWriteChat]),
+    ("set_dated_title", &[Scope::ChannelManageBroadcast]),
+    ("fulfill_redemptions", &[Scope::ChannelManageRedemptions]),
+    ("marker_category", &[]),
+    (
+        "post_standings",
+        &[
+            Scope::ChannelReadPolls,
+            Scope::ChannelReadPredictions,
+            Scope::UserWriteChat,
+        ],
+    ),
+    ("post_rank_change", &[Scope::UserWriteChat]),
+    (
+        "announce_goal",
+        &[Scope::ChannelReadGoals, Scope::ModeratorManageAnnouncements],
+    ),
+    ("raid_slow_mode", &[Scope::ModeratorManageChatSettings]),
+    ("cycle_announcement", &[Scope::ModeratorManageAnnouncements]),
+    (
+        "sub_hype",
+        &[
+            Scope::ModeratorManageAnnouncements,
+            Scope::ModeratorManageChatSettings,
+        ],
+    ),
+    (
+        "toggle_reward_auto_fulfill",
+        &[Scope::ChannelManageRedemptions],
+    ),
+    ("post_chat_status", &[Scope::UserWriteChat]),
+    ("post_raid_thanks", &[Scope::UserWriteChat]),
+    ("export_markers", &[]),
+    ("cycle_chat_delay", &[Scope::ModeratorManageChatSettings]),
+    (
+        "marked_message",
+        &[Scope::UserWriteChat, Scope::ChannelManageBroadcast],
+    ),
+    (
+        "clear_chat_with_warning",
+        &[
+            Scope::ModeratorManageAnnouncements,
+            Scope::ModeratorManageChatMessages,
+        ],
+    ),
+    ("post_random_clip", &[Scope::UserWriteChat]),
+    (
+        "focus_mode",
+        &[Scope::ModeratorManageChatSettings, Scope::UserWriteChat],
+    ),
+    ("temp_vip", &[Scope::ChannelManageVips]),
+    ("mark_viewer_count", &[Scope::ChannelManageBroadcast]),
+    ("post_next_stream", &[Scope::UserWriteChat]),
+    ("refresh_all", &[]),
+    ("timeout_user", &[Scope::ModeratorManageBannedUsers]),
+    ("ban_user", &[Scope::ModeratorManageBannedUsers]),
+    ("announcement", &[Scope::ModeratorManageAnnouncements]),
+    ("start_raid", &[Scope::ChannelManageRaids]),
+    ("cancel_raid", &[Scope::ChannelManageRaids]),
+    ("shoutout", &[Scope::ModeratorManageShoutouts]),
+    ("update_category", &[Scope::ChannelManageBroadcast]),
+    ("create_poll", &[Scope::ChannelManagePolls]),
+    ("end_poll", &[Scope::ChannelManagePolls]),
+    ("create_prediction", &[Scope::ChannelManagePredictions]),
+    ("resolve_prediction", &[Scope::ChannelManagePredictions]),
+    ("add_vip", &[Scope::ChannelManageVips]),
+    ("remove_vip", &[Scope::ChannelManageVips]),
+    ("add_moderator", &[Scope::ChannelManageModerators]),
+    ("remove_moderator", &[Scope::ChannelManageModerators]),
+    ("warn_user", &[Scope::ModeratorManageWarnings]),
+    ("snooze_ad", &[Scope::ChannelManageAds]),
+    ("delete_message", &[Scope::ModeratorManageChatMessages]),
+    ("block_user", &[Scope::UserManageBlockedUsers]),
+    ("unblock_user", &[Scope::UserManageBlockedUsers]),
+    ("set_slow_mode", &[Scope::ModeratorManageChatSettings]),
+    ("set_follower_mode", &[Scope::ModeratorManageChatSettings]),
+    ("unique_chat", &[Scope::ModeratorManageChatSettings]),
+    ("lockdown", &[Scope::ModeratorManageChatSettings]),
+    ("unlock", &[Scope::ModeratorManageChatSettings]),
+];
+
+/// Conditions that must be met before any action executes, read from the
+/// properties shared by every action
+#[derive(Deserialize, Default, Clone, Copy)]
+pub struct ActionConditions {
+    // Minimum number of viewers required for the action to run
+    #[serde(default)]
+    pub min_viewers: Option<usize>,
+}
+
+impl ActionConditions {
+    pub fn from_properties(
+        properties: &serde_json::Value,
+    ) -> Result<ActionConditions, TwitchError> {
+        if properties.is_null() {
+            return Ok(ActionConditions::default());
+        }
+
+        serde_json::from_value(properties.clone())
+            .map_err(|cause| TwitchError::InvalidProperties(format!("invalid conditions: {cause}")))
+    }
+}
 
 pub enum Action {
     SendMessage(SendMessageProperties),
     ClearChat,
-    EmoteOnly,
-    FollowerOnly,
-    SubOnly,
-    SlowMode,
+    EmoteOnly(ToggleProperties),
+    FollowerOnly(ToggleProperties),
+    SubOnly(ToggleProperties),
+    SlowMode(ToggleProperties),
     AdBreak(AdBreakProperties),
     Marker(MarkerProperties),
     CreateClip,
     OpenClip,
     ViewerCount,
+    GoLive(GoLiveProperties),
+    CaptureMoment(CaptureMomentProperties),
+    ToggleChatColor(ToggleColorProperties),
+    PostCategoryRank,
+    ClipAndEdit,
+    ToggleMature,
+    PostFollowersGained,
+    PostSocials,
+    LurkMode(LurkModeProperties),
+    ClipAndPost(ClipAndPostProperties),
+    SetDatedTitle(DatedTitleProperties),
+    FulfillRedemptions(RedemptionProperties),
+    MarkerCategory(MarkerCategoryProperties),
+    PostStandings(StandingsProperties),
+    PostRankChange,
+    AnnounceGoal(AnnounceGoalProperties),
+    RaidSlowMode(RaidSlowModeProperties),
+    CycleAnnouncement(CycleAnnouncementProperties),
+    SubHype(SubHypeProperties),
+    ToggleRewardAutoFulfill(RewardProperties),
+    PostChatStatus(ChatStatusProperties),
+    PostRaidThanks(RaidThanksProperties),
+    ExportMarkers(ExportProperties),
+    CycleChatDelay(CycleChatDelayProperties),
+    MarkedMessage(MarkedMessageProperties),
+    ClearChatWithWarning(ClearChatWarningProperties),
+    PostRandomClip(RandomClipProperties),
+    FocusMode(FocusModeProperties),
+    TempVip(TempVipProperties),
+    MarkViewerCount,
+    PostNextStream(NextStreamProperties),
+    RefreshAll,
+    Timeout(TimeoutProperties),
+    Ban(BanProperties),
+    Announcement(AnnouncementProperties),
+    StartRaid(RaidProperties),
+    CancelRaid,
+    Shoutout(ShoutoutProperties),
+    UpdateCategory(UpdateCategoryProperties),
+    CreatePoll(PollProperties),
+    EndPoll(EndPollProperties),
+    CreatePrediction(PredictionProperties),
+    ResolvePrediction(ResolvePredictionProperties),
+    AddVip(VipProperties),
+    RemoveVip(VipProperties),
+    AddModerator(ModProperties),
+    RemoveModerator(ModProperties),
+    WarnUser(WarnProperties),
+    SnoozeAd,
+    DeleteMessage(DeleteMessageProperties),
+    BlockUser(BlockProperties),
+    UnblockUser(BlockProperties),
+    SetSlowMode(SlowModeProperties),
+    SetFollowerMode(FollowerModeProperties),
+    UniqueChat,
+    Lockdown,
+    Unlock,
 }
 
 impl Action {
+    /// Critical moderation actions bypass the concurrent action limit so they
+    /// are never stuck behind other actions
+    pub fn is_critical(&self) -> bool {
+        matches!(
+            self,
+            Action::ClearChat
+                | Action::EmoteOnly(_)
+                | Action::FollowerOnly(_)
+                | Action::SubOnly(_)
+                | Action::SlowMode(_)
+                | Action::RaidSlowMode(_)
+                | Action::CycleChatDelay(_)
+                | Action::Timeout(_)
+                | Action::Ban(_)
+                | Action::WarnUser(_)
+                | Action::DeleteMessage(_)
+                | Action::SetSlowMode(_)
+                | Action::SetFollowerMode(_)
+                | Action::UniqueChat
+                | Action::Lockdown
+                | Action::Unlock
+        )
+    }
+
     pub fn from_action(
         action_id: &str,
         properties: serde_json::Value,
     ) -> Option<Result<Action, serde_json::Error>> {
+        // Actions must declare their scopes to be usable
+        if !ACTION_SCOPES.iter().any(|(id, _)| *id == action_id) {
+            return None;
+        }
+
         Some(match action_id {
             "send_message" => serde_json::from_value(properties).map(Action::SendMessage),
             "clear_chat" => Ok(Action::ClearChat),
-            "emote_only" => Ok(Action::EmoteOnly),
-            "follower_only" => Ok(Action::FollowerOnly),
-            "sub_only" => Ok(Action::SubOnly),
-            "slow_mode" => Ok(Action::SlowMode),
+            "emote_only" => serde_json::from_value(properties).map(Action::EmoteOnly),
+            "follower_only" => serde_json::from_value(properties).map(Action::FollowerOnly),
+            "sub_only" => serde_json::from_value(properties).map(Action::SubOnly),
+            "slow_mode" => serde_json::from_value(properties).map(Action::SlowMode),
             "ad_break" => serde_json::from_value(properties).map(Action::AdBreak),
             "marker" => serde_json::from_value(properties).map(Action::Marker),
             "create_clip" => Ok(Action::CreateClip),
             "open_clip" => Ok(Action::OpenClip),
             "viewer_count" => Ok(Action::ViewerCount),
+            "go_live" => serde_json::from_value(properties).map(Action::GoLive),
+            "capture_moment" => serde_json::from_value(properties).map(Action::CaptureMoment),
+            "toggle_chat_color" => serde_json::from_value(properties).map(Action::ToggleChatColor),
+            "post_category_rank" => Ok(Action::PostCategoryRank),
+            "clip_and_edit" => Ok(Action::ClipAndEdit),
+            "toggle_mature" => Ok(Action::ToggleMature),
+            "post_followers_gained" => Ok(Action::PostFollowersGained),
+            "post_socials" => Ok(Action::PostSocials),
+            "lurk_mode" => serde_json::from_value(properties).map(Action::LurkMode),
+            "clip_and_post" => serde_json::from_value(properties).map(Action::ClipAndPost),
+            "set_dated_title" => serde_json::from_value(properties).map(Action::SetDatedTitle),
+            "fulfill_redemptions" => {
+                serde_json::from_value(properties).map(Action::FulfillRedemptions)
+            }
+            "marker_category" => serde_json::from_value(properties).map(Action::MarkerCategory),
+            "post_standings" => serde_json::from_value(properties).map(Action::PostStandings),
+            "post_rank_change" => Ok(Action::PostRankChange),
+            "announce_goal" => serde_json::from_value(properties).map(Action::AnnounceGoal),
+            "raid_slow_mode" => serde_json::from_value(properties).map(Action::RaidSlowMode),
+            "cycle_announcement" => {
+                serde_json::from_value(properties).map(Action::CycleAnnouncement)
+            }
+            "sub_hype" => serde_json::from_value(properties).map(Action::SubHype),
+            "toggle_reward_auto_fulfill" => {
+                serde_json::from_value(properties).map(Action::ToggleRewardAutoFulfill)
+            }
+            "post_chat_status" => serde_json::from_value(properties).map(Action::PostChatStatus),
+            "post_raid_thanks" => serde_json::from_value(properties).map(Action::PostRaidThanks),
+            "export_markers" => serde_json::from_value(properties).map(Action::ExportMarkers),
+            "cycle_chat_delay" => serde_json::from_value(properties).map(Action::CycleChatDelay),
+            "marked_message" => serde_json::from_value(properties).map(Action::MarkedMessage),
+            "clear_chat_with_warning" => {
+                serde_json::from_value(properties).map(Action::ClearChatWithWarning)
+            }
+            "post_random_clip" => serde_json::from_value(properties).map(Action::PostRandomClip),
+            "focus_mode" => serde_json::from_value(properties).map(Action::FocusMode),
+            "temp_vip" => serde_json::from_value(properties).map(Action::TempVip),
+            "mark_viewer_count" => Ok(Action::MarkViewerCount),
+            "post_next_stream" => serde_json::from_value(properties).map(Action::PostNextStream),
+            "refresh_all" => Ok(Action::RefreshAll),
+            "timeout_user" => serde_json::from_value(properties).map(Action::Timeout),
+            "ban_user" => serde_json::from_value(properties).map(Action::Ban),
+            "announcement" => serde_json::from_value(properties).map(Action::Announcement),
+            "start_raid" => serde_json::from_value(properties).map(Action::StartRaid),
+            "cancel_raid" => Ok(Action::CancelRaid),
+            "shoutout" => serde_json::from_value(properties).map(Action::Shoutout),
+            "update_category" => serde_json::from_value(properties).map(Action::UpdateCategory),
+            "create_poll" => serde_json::from_value(properties).map(Action::CreatePoll),
+            "end_poll" => serde_json::from_value(properties).map(Action::EndPoll),
+            "create_prediction" => serde_json::from_value(properties).map(Action::CreatePrediction),
+            "resolve_prediction" => {
+                serde_json::from_value(properties).map(Action::ResolvePrediction)
+            }
+            "add_vip" => serde_json::from_value(properties).map(Action::AddVip),
+            "remove_vip" => serde_json::from_value(properties).map(Action::RemoveVip),
+            "add_moderator" => serde_json::from_value(properties).map(Action::AddModerator),
+            "remove_moderator" => serde_json::from_value(properties).map(Action::RemoveModerator),
+            "warn_user" => serde_json::from_value(properties).map(Action::WarnUser),
+            "snooze_ad" => Ok(Action::SnoozeAd),
+            "delete_message" => serde_json::from_value(properties).map(Action::DeleteMessage),
+            "block_user" => serde_json::from_value(properties).map(Action::BlockUser),
+            "unblock_user" => serde_json::from_value(properties).map(Action::UnblockUser),
+            "set_slow_mode" => serde_json::from_value(properties).map(Action::SetSlowMode),
+            "set_follower_mode" => serde_json::from_value(properties).map(Action::SetFollowerMode),
+            "unique_chat" => Ok(Action::UniqueChat),
+            "lockdown" => Ok(Action::Lockdown),
+            "unlock" => Ok(Action::Unlock),
             _ => return None,
         })
     }
@@ -40,6 +338,28 @@ impl Action {
 #[derive(Deserialize)]
 pub struct SendMessageProperties {
     pub message: Option<String>,
+    // Prepends an @-mention of the latest follower or subscriber
+    pub mention: Option<Mention>,
+    // Retry with a suffix when twitch rejects the message as a duplicate
+    #[serde(default)]
+    pub avoid_duplicates: bool,
+    // Suffixes rotated through when retrying a duplicate message
+    #[serde(default)]
+    pub duplicate_suffixes: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Mention {
+    LatestFollower,
+    LatestSub,
+}
+
+#[derive(Deserialize)]
+pub struct ToggleProperties {
+    // Additional channels (by login) the setting is mirrored to
+    #[serde(default)]
+    pub channels: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -47,8 +367,495 @@ pub struct MarkerProperties {
     pub description: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct TimeoutProperties {
+    // Login of the user to timeout
+    pub username: Option<String>,
+    // Length of the timeout in seconds (max 1209600)
+    pub duration: Option<u32>,
+    // Reason shown for the timeout
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct FollowerModeProperties {
+    // Defaults to enabling follower only mode
+    pub enabled: Option<bool>,
+    // 0-129600 minutes users must follow for, the current duration is kept when unset
+    pub duration_minutes: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct SlowModeProperties {
+    // Defaults to enabling slow mode
+    pub enabled: Option<bool>,
+    // 3-120s wait between messages, the current wait time is kept when unset
+    pub wait_seconds: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct BlockProperties {
+    pub username: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteMessageProperties {
+    // ID of the chat message to delete
+    pub message_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct WarnProperties {
+    pub username: Option<String>,
+    // Reason shown to the user, required by twitch
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ModProperties {
+    pub username: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct VipProperties {
+    pub username: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ResolvePredictionProperties {
+    // LOCKED, RESOLVED or CANCELED
+    pub status: Option<String>,
+    // Index of the winning outcome (starting at 0), required when resolving
+    pub winning_outcome_index: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct PredictionProperties {
+    pub title: Option<String>,
+    // Between 2 and 10 outcomes
+    pub outcomes: Option<Vec<String>>,
+    // Time viewers can make predictions for (seconds)
+    pub window: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct EndPollProperties {
+    // TERMINATED to end the poll showing results, ARCHIVED to end and hide it
+    pub status: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PollProperties {
+    pub title: Option<String>,
+    // Between 2 and 5 choices
+    pub choices: Option<Vec<String>>,
+    // Duration of the poll (seconds)
+    pub duration: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateCategoryProperties {
+    // Name of the game/category to switch to
+    pub game_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ShoutoutProperties {
+    // Login of the channel to shoutout
+    pub target_login: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RaidProperties {
+    // Login of the channel to raid
+    pub target_login: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AnnouncementProperties {
+    pub message: Option<String>,
+    // Color of the announcement (blue, green, orange, purple or primary)
+    pub color: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BanProperties {
+    // Login of the user to ban
+    pub username: Option<String>,
+    // Reason shown for the ban
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct NextStreamProperties {
+    // Template for the message, supports {title} and {time}
+    pub template: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct TempVipProperties {
+    // Login of the chatter to grant VIP to
+    pub target_login: Option<String>,
+    // Minutes the chatter remains a VIP for
+    pub minutes: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct FocusModeProperties {
+    // Message posted when entering focus mode
+    pub enter_message: Option<String>,
+    // Message posted when leaving focus mode
+    pub exit_message: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RandomClipProperties {
+    // Template for the message, supports {url}, {title} and {creator}
+    pub template: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ClearChatWarningProperties {
+    // Warning announced before clearing, supports {seconds}
+    pub message: Option<String>,
+    // Seconds between the warning and clearing chat
+    pub delay: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct MarkedMessageProperties {
+    // Message posted to chat and used as the marker description
+    pub message: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct AdBreakProperties {
-    // 1-180s duration of the ad
+    // 1-180s duration of the ad, either seconds or a label (e.g "30s", "1m")
+    #[serde(default, deserialize_with = "deserialize_commercial_length")]
     pub length: Option<CommercialLength>,
 }
+
+/// Valid commercial lengths along with their duration in seconds and label
+const COMMERCIAL_LENGTHS: &[(CommercialLength, u64, &str)] = &[
+    (CommercialLength::Length30, 30, "30s"),
+    (CommercialLength::Length60, 60, "1m"),
+    (CommercialLength::Length90, 90, "1m30s"),
+    (CommercialLength::Length120, 120, "2m"),
+    (CommercialLength::Length150, 150, "2m30s"),
+    (CommercialLength::Length180, 180, "3m"),
+];
+
+/// Finds the valid commercial length nearest to the provided seconds, values
+/// outside the valid range are clamped to the shortest or longest length
+pub fn commercial_length_from_secs(secs: u64) -> CommercialLength {
+    COMMERCIAL_LENGTHS
+        .iter()
+        .min_by_key(|(_, length_secs, _)| length_secs.abs_diff(secs))
+        .map(|(length, _, _)| *length)
+        .unwrap_or(CommercialLength::Length30)
+}
+
+/// Parses a commercial length from a label (e.g "1m30s") or plain seconds
+/// (e.g "90"), seconds are clamped to the nearest valid length
+pub fn parse_commercial_length(value: &str) -> Option<CommercialLength> {
+    let value = value.trim().to_ascii_lowercase();
+
+    if let Some((length, _, _)) = COMMERCIAL_LENGTHS
+        .iter()
+        .find(|(_, _, label)| *label == value)
+    {
+        return Some(*length);
+    }
+
+    let secs = value.strip_suffix('s').unwrap_or(&value).parse().ok()?;
+    Some(commercial_length_from_secs(secs))
+}
+
+/// Provides the inspector label for a commercial length
+pub fn commercial_length_label(length: CommercialLength) -> &'static str {
+    COMMERCIAL_LENGTHS
+        .iter()
+        .find(|(other, _, _)| *other == length)
+        .map(|(_, _, label)| *label)
+        .unwrap_or("30s")
+}
+
+fn deserialize_commercial_length<'de, D>(
+    deserializer: D,
+) -> Result<Option<CommercialLength>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    let length = match value {
+        None | Some(serde_json::Value::Null) => None,
+        Some(serde_json::Value::Number(secs)) => {
+            let secs = secs.as_u64().unwrap_or_default();
+            Some(commercial_length_from_secs(secs))
+        }
+        Some(serde_json::Value::String(value)) => {
+            Some(parse_commercial_length(&value).ok_or_else(|| {
+                D::Error::custom(format!("invalid commercial length \"{value}\""))
+            })?)
+        }
+        Some(_) => return Err(D::Error::custom("expected commercial length")),
+    };
+
+    Ok(length)
+}
+
+#[derive(Deserialize)]
+pub struct GoLiveProperties {
+    // Each step is only performed when its property is set
+    pub title: Option<String>,
+    pub category: Option<String>,
+    pub announcement: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CaptureMomentProperties {
+    // Label shared by the clip, marker and log entry
+    pub label: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ToggleColorProperties {
+    // Named twitch color or hex color
+    pub first: Option<String>,
+    pub second: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LurkModeProperties {
+    // Message posted when entering lurk mode
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ClipAndPostProperties {
+    // Seconds to wait before checking if the clip is ready
+    pub delay: Option<u64>,
+    // Message template, {url} is replaced with the clip URL
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DatedTitleProperties {
+    // Title template, {date} is replaced with the current date
+    pub template: Option<String>,
+    // Format for the date (e.g "DD/MM/YYYY")
+    pub date_format: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RedemptionProperties {
+    // ID of a reward created by the plugin
+    pub reward_id: Option<String>,
+    // Status to set on the redemptions
+    pub status: Option<RedemptionStatus>,
+    // Number of redemptions to update, oldest first (Max 50)
+    pub count: Option<usize>,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RedemptionStatus {
+    #[default]
+    Fulfilled,
+    Canceled,
+}
+
+impl From<RedemptionStatus> for CustomRewardRedemptionStatus {
+    fn from(value: RedemptionStatus) -> Self {
+        match value {
+            RedemptionStatus::Fulfilled => CustomRewardRedemptionStatus::Fulfilled,
+            RedemptionStatus::Canceled => CustomRewardRedemptionStatus::Canceled,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MarkerCategoryProperties {
+    // Labels cycled through on each press
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct StandingsProperties {
+    // Template for the chat message, supports {title} and {standings}
+    pub template: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AnnounceGoalProperties {
+    // Template for the announcement, supports {percent}, {current}, {target} and {unit}
+    pub template: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RaidSlowModeProperties {
+    // 3-120s wait between messages while active
+    pub wait_time: Option<u64>,
+    // Minutes before the previous slow mode setting is restored
+    pub duration_minutes: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct CycleAnnouncementProperties {
+    // Announcements posted in order, one per press
+    #[serde(default)]
+    pub announcements: Vec<AnnouncementEntry>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct AnnouncementEntry {
+    pub message: String,
+    // blue, green, orange, purple or primary (default)
+    pub color: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SubHypeProperties {
+    // Announcement posted when pressed
+    pub message: Option<String>,
+    // Chat mode enabled during the celebration
+    pub mode: Option<ChatMode>,
+    // Seconds before the chat mode is restored
+    pub duration: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChatMode {
+    #[default]
+    EmoteOnly,
+    SubOnly,
+    FollowerOnly,
+    SlowMode,
+}
+
+impl ChatMode {
+    /// Whether the mode is enabled within the current settings
+    pub fn is_enabled(&self, settings: &ChatSettings) -> bool {
+        match self {
+            ChatMode::EmoteOnly => settings.emote_mode,
+            ChatMode::SubOnly => settings.subscriber_mode,
+            ChatMode::FollowerOnly => settings.follower_mode,
+            ChatMode::SlowMode => settings.slow_mode,
+        }
+    }
+
+    /// Sets whether the mode is enabled within the update body
+    pub fn set_enabled(&self, body: &mut UpdateChatSettingsBody, enabled: bool) {
+        match self {
+            ChatMode::EmoteOnly => body.emote_mode = Some(enabled),
+            ChatMode::SubOnly => body.subscriber_mode = Some(enabled),
+            ChatMode::FollowerOnly => body.follower_mode = Some(enabled),
+            ChatMode::SlowMode => body.slow_mode = Some(enabled),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RewardProperties {
+    // ID of a reward created by the plugin
+    pub reward_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ChatStatusProperties {
+    // Template for the message, supports {restrictions}
+    pub template: Option<String>,
+    // Post a message even when no restrictions are active
+    #[serde(default)]
+    pub post_when_clear: bool,
+}
+
+#[derive(Deserialize)]
+pub struct RaidThanksProperties {
+    // Template for the message, supports {raider} and {viewers}
+    pub template: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CycleChatDelayProperties {
+    // Delays to cycle through (seconds), 0 disables the chat delay
+    #[serde(default = "default_chat_delays")]
+    pub delays: Vec<u64>,
+}
+
+fn default_chat_delays() -> Vec<u64> {
+    vec![0, 2, 4, 6]
+}
+
+#[derive(Deserialize)]
+pub struct ExportProperties {
+    // Path to write the chapters file to
+    pub path: Option<String>,
+    // Format of the chapters file
+    #[serde(default)]
+    pub format: ChapterFormat,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seconds outside the valid range are clamped to the shortest or longest length
+    #[test]
+    fn commercial_length_out_of_range() {
+        assert_eq!(commercial_length_from_secs(0), CommercialLength::Length30);
+        assert_eq!(
+            commercial_length_from_secs(10_000),
+            CommercialLength::Length180
+        );
+    }
+
+    /// Seconds between two lengths use the nearest, ties use the shorter length
+    #[test]
+    fn commercial_length_nearest() {
+        assert_eq!(commercial_length_from_secs(45), CommercialLength::Length30);
+        assert_eq!(commercial_length_from_secs(50), CommercialLength::Length60);
+        assert_eq!(commercial_length_from_secs(100), CommercialLength::Length90);
+    }
+
+    /// Labels and plain seconds are both accepted
+    #[test]
+    fn parse_commercial_length_labels() {
+        assert_eq!(
+            parse_commercial_length("1m30s"),
+            Some(CommercialLength::Length90)
+        );
+        assert_eq!(
+            parse_commercial_length("90s"),
+            Some(CommercialLength::Length90)
+        );
+        assert_eq!(
+            parse_commercial_length(" 2M "),
+            Some(CommercialLength::Length120)
+        );
+        assert_eq!(
+            parse_commercial_length("0"),
+            Some(CommercialLength::Length30)
+        );
+        assert_eq!(parse_commercial_length("soon"), None);
+    }
+
+    /// Actions not declared in [ACTION_SCOPES] are rejected
+    #[test]
+    fn undeclared_action_rejected() {
+        assert!(Action::from_action("unknown", serde_json::json!({})).is_none());
+    }
+
+    /// Every declared action is accepted by [Action::from_action]
+    #[test]
+    fn every_declared_action_exists() {
+        for (id, _) in ACTION_SCOPES {
+            assert!(
+                Action::from_action(id, serde_json::json!({})).is_some(),
+                "ACTION_SCOPES entry \"{id}\" is not an action"
+            );
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Context;
+use twitch_api::{
+    helix::chat::AnnouncementColor,
+    types::{HexColor, NamedUserColor},
+};
+
+/// Parses a chat color from either one of the named twitch colors
+/// (e.g "blue_violet") or a hex color (e.g "#9146FF")
+pub fn parse_chat_color(value: &str) -> anyhow::Result<NamedUserColor<'static>> {
+    let value = value.trim();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.chars().all(|char| char.is_ascii_hexdigit()) {
+            anyhow::bail!("invalid hex color \"{value}\"");
+        }
+
+        return Ok(NamedUserColor::from(HexColor::from(value)));
+    }
+
+    let name = value.to_ascii_lowercase().replace([' ', '-'], "_");
+    let color = NamedUserColor::all()
+        .iter()
+        .find(|color| color.to_string() == name)
+        .with_context(|| format!("unknown chat color \"{value}\""))?;
+
+    Ok(color.clone())
+}
+
+/// Parses an announcement color (e.g "blue"), an empty value uses the
+/// channels accent color
+pub fn parse_announcement_color(value: &str) -> anyhow::Result<AnnouncementColor> {
+    let color = match value.trim().to_ascii_lowercase().as_str() {
+        "" | "primary" => AnnouncementColor::Primary,
+        "blue" => AnnouncementColor::Blue,
+        "green" => AnnouncementColor::Green,
+        "orange" => AnnouncementColor::Orange,
+        "purple" => AnnouncementColor::Purple,
+        _ => anyhow::bail!("unknown announcement color \"{value}\""),
+    };
+
+    Ok(color)
+}
@@ -0,0 +1,137 @@
+use std::{
+    cell::RefCell,
+    time::{Duration, Instant},
+};
+
+use tilepad_plugin_sdk::Display;
+
+/// Duration a display is considered active for after its last request
+const DISPLAY_ALIVE_DURATION: Duration = Duration::from_secs(5);
+
+/// Shortest refresh interval a display is allowed to request
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Value polled from twitch for displays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    ViewCount,
+    GoalProgress,
+    ClipsToday,
+    PendingRedemptions,
+    ActiveInteractions,
+    CategoryViewers,
+    FollowerCount,
+    Uptime,
+    ChannelInfo,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 9] = [
+        Metric::ViewCount,
+        Metric::GoalProgress,
+        Metric::ClipsToday,
+        Metric::PendingRedemptions,
+        Metric::ActiveInteractions,
+        Metric::CategoryViewers,
+        Metric::FollowerCount,
+        Metric::Uptime,
+        Metric::ChannelInfo,
+    ];
+
+    /// Interval the metric is refreshed at when no display requested
+    /// a specific interval
+    pub fn default_interval(&self) -> Duration {
+        match self {
+            // Configurable through the plugin properties
+            Metric::ViewCount => Duration::from_secs(5),
+            // Goals change less frequently than viewers
+            Metric::GoalProgress => Duration::from_secs(10),
+            // Clips are counted across several pages
+            Metric::ClipsToday => Duration::from_secs(60),
+            Metric::PendingRedemptions => Duration::from_secs(15),
+            Metric::ActiveInteractions => Duration::from_secs(15),
+            // Streams are counted across several pages
+            Metric::CategoryViewers => Duration::from_secs(120),
+            Metric::FollowerCount => Duration::from_secs(60),
+            // Uptime is calculated from the start time when requested, the
+            // start time is only checked to notice the stream going online
+            Metric::Uptime => Duration::from_secs(60),
+            // Channel information rarely changes
+            Metric::ChannelInfo => Duration::from_secs(30),
+        }
+    }
+}
+
+/// Collection of displays that are currently requesting a value, used to
+/// only poll twitch for values that are actually visible
+#[derive(Default)]
+pub struct DisplayRegistry {
+    displays: RefCell<Vec<ActiveDisplay>>,
+}
+
+pub struct ActiveDisplay {
+    display: Display,
+    /// Value the display is requesting
+    metric: Metric,
+    last_alive: Instant,
+    /// Interval the display requested the value be refreshed at
+    refresh_interval: Option<Duration>,
+}
+
+impl DisplayRegistry {
+    /// Marks the display as active for the metric, adding it if not already
+    /// present. The requested refresh interval is clamped to [MIN_REFRESH_INTERVAL]
+    pub fn push(&self, display: &Display, metric: Metric, refresh_secs: Option<u64>) {
+        let displays = &mut *self.displays.borrow_mut();
+        let now = Instant::now();
+        let refresh_interval =
+            refresh_secs.map(|secs| Duration::from_secs(secs).max(MIN_REFRESH_INTERVAL));
+
+        if let Some(existing) = displays
+            .iter_mut()
+            .find(|other| other.metric == metric && other.display.ctx.eq(&display.ctx))
+        {
+            existing.last_alive = now;
+            existing.refresh_interval = refresh_interval;
+        } else {
+            displays.push(ActiveDisplay {
+                display: display.clone(),
+                metric,
+                last_alive: now,
+                refresh_interval,
+            });
+        }
+    }
+
+    /// Shortest refresh interval requested by any of the active displays of
+    /// the metric, [None] when no display requested a specific interval
+    pub fn refresh_interval(&self, metric: Metric) -> Option<Duration> {
+        let now = Instant::now();
+        self.displays
+            .borrow()
+            .iter()
+            .filter(|display| display.metric == metric)
+            .filter(|display| now.duration_since(display.last_alive) < DISPLAY_ALIVE_DURATION)
+            .filter_map(|display| display.refresh_interval)
+            .min()
+    }
+
+    /// Whether any display of the metric is still active
+    pub fn is_active(&self, metric: Metric) -> bool {
+        let now = Instant::now();
+        self.displays.borrow().iter().any(|display| {
+            display.metric == metric
+                && now.duration_since(display.last_alive) < DISPLAY_ALIVE_DURATION
+        })
+    }
+
+    /// Removes any displays that are no longer active returning
+    /// the number of remaining active displays
+    pub fn active_count(&self) -> usize {
+        let now = Instant::now();
+        let displays = &mut *self.displays.borrow_mut();
+        displays.retain(|display| now.duration_since(display.last_alive) < DISPLAY_ALIVE_DURATION);
+
+        displays.len()
+    }
+}
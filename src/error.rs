@@ -0,0 +1,157 @@
+use std::fmt;
+
+use tilepad_plugin_sdk::tracing;
+use twitch_api::{
+    helix::{
+        ClientRequestError, HelixRequestDeleteError, HelixRequestGetError, HelixRequestPatchError,
+        HelixRequestPostError, HelixRequestPutError, Scope,
+    },
+    twitch_oauth2::tokens::errors::ValidationError,
+};
+
+/// Error produced when authenticating with twitch
+#[derive(Debug)]
+pub enum AuthError {
+    /// Twitch rejected the access token, it should be discarded
+    InvalidToken(anyhow::Error),
+    /// Twitch could not be reached, the attempt may succeed when retried
+    Transient(anyhow::Error),
+    /// Twitch did not respond within the auth timeout
+    Timeout,
+    /// Token is valid but was not granted scopes required for its role
+    MissingScopes(Vec<Scope>),
+}
+
+impl AuthError {
+    /// Whether the auth attempt is worth retrying
+    pub fn is_transient(&self) -> bool {
+        matches!(self, AuthError::Transient(_))
+    }
+
+    /// Whether the access token is unusable and should be discarded, tokens
+    /// missing scopes are still valid and are kept until authorized again
+    pub fn is_invalid_token(&self) -> bool {
+        matches!(self, AuthError::InvalidToken(_))
+    }
+}
+
+impl<RE> From<ValidationError<RE>> for AuthError
+where
+    RE: std::error::Error + Send + Sync + 'static,
+{
+    fn from(value: ValidationError<RE>) -> Self {
+        match value {
+            ValidationError::Request(_) => AuthError::Transient(value.into()),
+            _ => AuthError::InvalidToken(value.into()),
+        }
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::InvalidToken(error) => write!(f, "invalid access token: {error}"),
+            AuthError::Transient(error) => write!(f, "failed to reach twitch: {error}"),
+            AuthError::Timeout => f.write_str("timed out waiting for twitch"),
+            AuthError::MissingScopes(scopes) => {
+                let scopes: Vec<&str> = scopes.iter().map(|scope| scope.as_str()).collect();
+                write!(f, "token is missing required scopes: {}", scopes.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuthError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AuthError::InvalidToken(error) | AuthError::Transient(error) => Some(error.as_ref()),
+            AuthError::Timeout | AuthError::MissingScopes(_) => None,
+        }
+    }
+}
+
+/// Error produced when executing an action
+#[derive(Debug)]
+pub enum TwitchError {
+    /// Action requires the user to be authenticated
+    NotAuthenticated,
+    /// Action properties are missing or invalid
+    InvalidProperties(String),
+    /// There was nothing for the action to act upon (e.g no active goal)
+    NothingToDo(&'static str),
+    /// Twitch responded with a body that could not be understood, usually
+    /// caused by twitch changing the shape of an endpoint
+    UnexpectedResponse(anyhow::Error),
+    /// Request to twitch failed
+    Request(anyhow::Error),
+}
+
+impl From<anyhow::Error> for TwitchError {
+    fn from(value: anyhow::Error) -> Self {
+        if let Some(body) = unexpected_response_body(&value) {
+            tracing::debug!(%body, "unexpected response body");
+            return TwitchError::UnexpectedResponse(value);
+        }
+
+        TwitchError::Request(value)
+    }
+}
+
+/// Finds the raw response body when the error was caused by a helix response
+/// that failed to deserialize, or a PUT/DELETE response twitch_api rejected
+/// as invalid (DELETE responses are never deserialized)
+fn unexpected_response_body(error: &anyhow::Error) -> Option<&str> {
+    error.chain().find_map(|cause| {
+        let cause = cause.downcast_ref::<ClientRequestError<reqwest::Error>>()?;
+        match cause {
+            ClientRequestError::HelixRequestGetError(HelixRequestGetError::DeserializeError(
+                body,
+                ..,
+            ))
+            | ClientRequestError::HelixRequestPostError(HelixRequestPostError::DeserializeError(
+                body,
+                ..,
+            ))
+            | ClientRequestError::HelixRequestPatchError(
+                HelixRequestPatchError::DeserializeError(body, ..),
+            )
+            | ClientRequestError::HelixRequestPutError(HelixRequestPutError::DeserializeError(
+                body,
+                ..,
+            ))
+            | ClientRequestError::HelixRequestPutError(HelixRequestPutError::InvalidResponse {
+                response: body,
+                ..
+            })
+            | ClientRequestError::HelixRequestDeleteError(
+                HelixRequestDeleteError::InvalidResponse { response: body, .. },
+            ) => Some(body.as_str()),
+            _ => None,
+        }
+    })
+}
+
+impl fmt::Display for TwitchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TwitchError::NotAuthenticated => f.write_str("not authenticated"),
+            TwitchError::InvalidProperties(reason) => write!(f, "invalid properties: {reason}"),
+            TwitchError::NothingToDo(reason) => f.write_str(reason),
+            TwitchError::UnexpectedResponse(error) => {
+                write!(f, "unexpected response from twitch: {error}")
+            }
+            TwitchError::Request(error) => write!(f, "request failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for TwitchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TwitchError::UnexpectedResponse(error) | TwitchError::Request(error) => {
+                Some(error.as_ref())
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use futures_util::StreamExt;
+use tilepad_plugin_sdk::tracing;
+use tokio::{task::spawn_local, time::sleep};
+use tokio_tungstenite::{connect_async, tungstenite::Message as WsMessage};
+use twitch_api::eventsub::{
+    Event, EventsubWebsocketData, Message, Payload, ReconnectPayload, WelcomePayload,
+};
+
+use crate::state::{IncomingRaid, State};
+
+/// Websocket URL for the twitch EventSub service
+pub const EVENTSUB_WEBSOCKET_URL: &str = "wss://eventsub.wss.twitch.tv/ws";
+
+/// Delay before attempting to reconnect after the connection is lost
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Background task maintaining an EventSub websocket connection while
+/// the user is authenticated
+pub async fn run_eventsub(state: Rc<State>) {
+    let mut url = EVENTSUB_WEBSOCKET_URL.to_string();
+    let mut subscribe = true;
+
+    loop {
+        if state.get_user_token().is_none() {
+            sleep(RECONNECT_DELAY).await;
+            continue;
+        }
+
+        match run_eventsub_session(&state, &url, subscribe).await {
+            // Twitch requested we move to a new connection, existing
+            // subscriptions are carried over to the new connection
+            Ok(Some(reconnect_url)) => {
+                url = reconnect_url;
+                subscribe = false;
+                continue;
+            }
+            Ok(None) => {}
+            Err(error) => {
+                tracing::error!(?error, "eventsub connection failed");
+            }
+        }
+
+        url = EVENTSUB_WEBSOCKET_URL.to_string();
+        subscribe = true;
+        sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Runs a single EventSub websocket session, returns the URL to reconnect
+/// to when twitch requests a reconnect
+async fn run_eventsub_session(
+    state: &Rc<State>,
+    url: &str,
+    subscribe: bool,
+) -> anyhow::Result<Option<String>> {
+    let (mut socket, _) = connect_async(url)
+        .await
+        .context("failed to connect to eventsub")?;
+
+    while let Some(message) = socket.next().await {
+        // Session is no longer needed once logged out
+        if state.get_user_token().is_none() {
+            return Ok(None);
+        }
+
+        let text = match message? {
+            WsMessage::Text(text) => text,
+            WsMessage::Close(_) => return Ok(None),
+            _ => continue,
+        };
+
+        let data = match Event::parse_websocket(&text) {
+            Ok(value) => value,
+            Err(error) => {
+                tracing::warn!(?error, "failed to parse eventsub message");
+                continue;
+            }
+        };
+
+        match data {
+            EventsubWebsocketData::Welcome {
+                payload: WelcomePayload { session, .. },
+                ..
+            } => {
+                if subscribe {
+                    state.subscribe_eventsub(&session.id).await?;
+                }
+            }
+            EventsubWebsocketData::Reconnect {
+                payload: ReconnectPayload { session, .. },
+                ..
+            } => {
+                let reconnect_url = session
+                    .reconnect_url
+                    .context("reconnect message missing url")?;
+                return Ok(Some(reconnect_url.to_string()));
+            }
+            EventsubWebsocketData::Notification { payload, .. } => {
+                handle_event(state, payload);
+            }
+            EventsubWebsocketData::Revocation { payload, .. } => {
+                tracing::warn!(?payload, "eventsub subscription revoked");
+            }
+            _ => {}
+        }
+    }
+
+    Ok(None)
+}
+
+/// Handles a notification event from EventSub
+fn handle_event(state: &Rc<State>, event: Event) {
+    match event {
+        Event::ChannelFollowV2(Payload {
+            message: Message::Notification(notification),
+            ..
+        }) => {
+            tracing::debug!(user = %notification.user_login, "received follow");
+            state.increment_followers_gained();
+            state.set_latest_follower(notification.user_login.to_string());
+        }
+        Event::ChannelSubscribeV1(Payload {
+            message: Message::Notification(notification),
+            ..
+        }) => {
+            tracing::debug!(user = %notification.user_login, "received subscription");
+            state.set_latest_subscriber(notification.user_login.to_string());
+        }
+        Event::ChannelRaidV1(Payload {
+            message: Message::Notification(notification),
+            ..
+        }) => {
+            let raid = IncomingRaid {
+                from_login: notification.from_broadcaster_user_login.to_string(),
+                viewers: notification.viewers,
+                received_at: Instant::now(),
+            };
+
+            tracing::debug!(?raid, "received raid");
+            state.set_last_raid(raid.clone());
+
+            let state = state.clone();
+            spawn_local(async move {
+                if let Err(error) = state.apply_raid_defense(&raid).await {
+                    tracing::error!(?error, "failed to apply raid defense");
+                }
+            });
+        }
+        Event::StreamOnlineV1(Payload {
+            message: Message::Notification(_),
+            ..
+        }) => {
+            tracing::debug!("stream started");
+            state.reset_followers_gained();
+            state.set_stream_online();
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,901 @@
+use std::{fmt, path::Path, rc::Rc, time::Duration};
+
+use tilepad_plugin_sdk::tracing;
+use tokio::{task::spawn_local, time::sleep};
+use twitch_api::{
+    helix::{chat::AnnouncementColor, polls::PollStatus, predictions::PredictionStatus},
+    types::{CommercialLength, ContentClassificationId, RewardId},
+};
+
+use crate::{
+    action::{Action, ActionConditions, Mention, commercial_length_label},
+    color::{parse_announcement_color, parse_chat_color},
+    error::TwitchError,
+    state::{
+        ALLOWED_CHAT_DELAYS, DEFAULT_CHAT_STATUS_TEMPLATE, DEFAULT_CLEAR_CHAT_WARNING,
+        DEFAULT_DATE_FORMAT, DEFAULT_FOCUS_ENTER_MESSAGE, DEFAULT_FOCUS_EXIT_MESSAGE,
+        DEFAULT_GOAL_TEMPLATE, DEFAULT_NEXT_STREAM_TEMPLATE, DEFAULT_POLL_DURATION,
+        DEFAULT_PREDICTION_WINDOW, DEFAULT_RAID_THANKS_TEMPLATE, DEFAULT_RANDOM_CLIP_TEMPLATE,
+        DEFAULT_STANDINGS_TEMPLATE, DEFAULT_SUB_HYPE_MESSAGE, MAX_CLEAR_CHAT_DELAY,
+        POLL_CHOICES_RANGE, POLL_DURATION_RANGE, PREDICTION_OUTCOMES_RANGE,
+        PREDICTION_WINDOW_RANGE, State,
+    },
+    template::expand_template,
+};
+
+/// Result of successfully executing an action
+#[derive(Debug)]
+pub enum ActionOutcome {
+    /// Action completed without anything further to report
+    Completed,
+    /// Message was posted to chat (or as an announcement)
+    Posted { message: String },
+    /// Setting was toggled, provides the new state of the setting
+    Toggled { enabled: bool },
+    /// Clip was created
+    Clip { id: String, edit_url: String },
+    /// Marker was created
+    Marker { id: String },
+    /// Action produced a URL that should be opened for the user
+    OpenUrl { url: String },
+    /// Number of items affected by the action
+    Count { count: usize },
+    /// Value was selected (e.g the next item of a cycling list)
+    Selected { value: String },
+}
+
+impl fmt::Display for ActionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionOutcome::Completed => f.write_str("completed"),
+            ActionOutcome::Posted { message } => write!(f, "posted \"{message}\""),
+            ActionOutcome::Toggled { enabled: true } => f.write_str("enabled"),
+            ActionOutcome::Toggled { enabled: false } => f.write_str("disabled"),
+            ActionOutcome::Clip { id, edit_url } => write!(f, "created clip {id} ({edit_url})"),
+            ActionOutcome::Marker { id } => write!(f, "created marker {id}"),
+            ActionOutcome::OpenUrl { url } => write!(f, "opened {url}"),
+            ActionOutcome::Count { count } => write!(f, "{count}"),
+            ActionOutcome::Selected { value } => write!(f, "selected {value}"),
+        }
+    }
+}
+
+impl State {
+    /// Executes the action returning the outcome, `cycle_key` identifies the
+    /// source of the action for actions that cycle through values.
+    ///
+    /// The action is skipped when the `conditions` are not met, the viewer
+    /// count condition is checked against the cached view count
+    ///
+    /// Actions that restore settings after a delay spawn a background task
+    /// for the restore and return once the settings are applied
+    pub async fn execute_action(
+        self: &Rc<Self>,
+        action: Action,
+        conditions: ActionConditions,
+        cycle_key: &str,
+    ) -> Result<ActionOutcome, TwitchError> {
+        if self.get_user_token().is_none() {
+            return Err(TwitchError::NotAuthenticated);
+        }
+
+        if let Some(min_viewers) = conditions.min_viewers {
+            let viewers = self.condition_view_count().await;
+            if viewers < min_viewers {
+                tracing::info!(
+                    viewers,
+                    min_viewers,
+                    "skipping action below minimum viewers"
+                );
+                return Err(TwitchError::NothingToDo("not enough viewers"));
+            }
+        }
+
+        // Held until the action completes
+        let _slot = match action.is_critical() {
+            true => None,
+            false => self.acquire_action_slot().await,
+        };
+
+        let outcome = match action {
+            Action::SendMessage(properties) => {
+                let mut message = match properties.message {
+                    Some(value) => self.expand_template(&value),
+                    None => return Err(TwitchError::InvalidProperties("missing message".into())),
+                };
+
+                // Mention is omitted when nobody has followed/subscribed yet
+                let mention = match properties.mention {
+                    Some(Mention::LatestFollower) => self.latest_follower(),
+                    Some(Mention::LatestSub) => self.latest_subscriber(),
+                    None => None,
+                };
+
+                if let Some(login) = mention {
+                    message = format!("@{login} {message}");
+                }
+
+                if properties.avoid_duplicates {
+                    message = self
+                        .send_chat_message_avoiding_duplicates(
+                            &message,
+                            &properties.duplicate_suffixes,
+                        )
+                        .await?;
+                } else {
+                    self.send_chat_message(&message).await?;
+                }
+
+                ActionOutcome::Posted { message }
+            }
+            Action::ClearChat => {
+                self.clear_chat().await?;
+                ActionOutcome::Completed
+            }
+            Action::EmoteOnly(properties) => {
+                self.toggle_emote_only(&properties.channels).await?;
+                ActionOutcome::Completed
+            }
+            Action::FollowerOnly(properties) => {
+                self.toggle_follower_only(&properties.channels).await?;
+                ActionOutcome::Completed
+            }
+            Action::SubOnly(properties) => {
+                self.toggle_sub_only(&properties.channels).await?;
+                ActionOutcome::Completed
+            }
+            Action::SlowMode(properties) => {
+                self.toggle_slow_mode(&properties.channels).await?;
+                ActionOutcome::Completed
+            }
+            Action::AdBreak(properties) => {
+                let length = properties.length.unwrap_or(CommercialLength::Length30);
+                self.start_comercial(length).await?;
+
+                tracing::info!(length = commercial_length_label(length), "started ad break");
+                ActionOutcome::Completed
+            }
+            Action::Marker(properties) => {
+                let description =
+                    non_empty(properties.description.as_deref()).map(|value| value.to_string());
+                let marker = self.create_categorized_marker(description).await?;
+
+                ActionOutcome::Marker {
+                    id: marker.id.to_string(),
+                }
+            }
+            Action::CreateClip => {
+                let clip = self
+                    .create_clip()
+                    .await?
+                    .into_iter()
+                    .next()
+                    .ok_or(TwitchError::NothingToDo("clip creation returned no clip"))?;
+
+                ActionOutcome::Clip {
+                    id: clip.id.to_string(),
+                    edit_url: clip.edit_url,
+                }
+            }
+            Action::OpenClip => ActionOutcome::Completed,
+            // No associated action (Maybe refresh manually when tapped?)
+            Action::ViewerCount => ActionOutcome::Completed,
+            Action::GoLive(properties) => {
+                self.go_live(
+                    non_empty(properties.title.as_deref()),
+                    non_empty(properties.category.as_deref()),
+                    non_empty(properties.announcement.as_deref()),
+                )
+                .await?;
+                ActionOutcome::Completed
+            }
+            Action::CaptureMoment(properties) => {
+                self.capture_moment(properties.label.unwrap_or_default())
+                    .await?;
+                ActionOutcome::Completed
+            }
+            Action::ToggleChatColor(properties) => {
+                let first = parse_chat_color(properties.first.as_deref().unwrap_or_default())
+                    .map_err(|error| TwitchError::InvalidProperties(error.to_string()))?;
+                let second = parse_chat_color(properties.second.as_deref().unwrap_or_default())
+                    .map_err(|error| TwitchError::InvalidProperties(error.to_string()))?;
+
+                let color = self.toggle_chat_color(first, second).await?;
+                ActionOutcome::Selected {
+                    value: color.to_string(),
+                }
+            }
+            Action::PostCategoryRank => {
+                let rank = self.post_category_rank().await?;
+                ActionOutcome::Count { count: rank }
+            }
+            Action::PostRankChange => {
+                let rank = self.post_category_rank_change().await?;
+                ActionOutcome::Count { count: rank }
+            }
+            Action::AnnounceGoal(properties) => {
+                let template =
+                    non_empty(properties.template.as_deref()).unwrap_or(DEFAULT_GOAL_TEMPLATE);
+
+                if !self.announce_goal_progress(template).await? {
+                    return Err(TwitchError::NothingToDo("no active goal to announce"));
+                }
+
+                ActionOutcome::Completed
+            }
+            Action::RaidSlowMode(properties) => {
+                let wait_time = properties.wait_time.unwrap_or(60);
+                let duration = Duration::from_secs(properties.duration_minutes.unwrap_or(5) * 60);
+                let previous = self.apply_slow_mode(wait_time).await?;
+
+                tracing::info!(wait_time, ?duration, "started raid slow mode");
+
+                let state = self.clone();
+                let generation = self.settings_generation();
+
+                spawn_local(async move {
+                    sleep(duration).await;
+
+                    // Settings were changed while waiting, keep the newer settings
+                    if !state.is_settings_generation_current(generation) {
+                        tracing::info!("chat settings changed, skipping slow mode restore");
+                        return;
+                    }
+
+                    match state.restore_slow_mode(previous).await {
+                        Ok(_) => tracing::info!(?previous, "restored slow mode after raid"),
+                        Err(error) => tracing::error!(?error, "failed to restore slow mode"),
+                    }
+                });
+
+                ActionOutcome::Toggled { enabled: true }
+            }
+            Action::CycleAnnouncement(properties) => {
+                // Validate every color up front so a bad entry is noticed immediately
+                let colors = properties
+                    .announcements
+                    .iter()
+                    .map(|entry| {
+                        parse_announcement_color(entry.color.as_deref().unwrap_or_default())
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .map_err(|error| TwitchError::InvalidProperties(error.to_string()))?;
+
+                if properties.announcements.is_empty() {
+                    return Err(TwitchError::InvalidProperties("no announcements".into()));
+                }
+
+                let index = self.next_cycle_index(cycle_key, colors.len());
+                let message = self.expand_template(&properties.announcements[index].message);
+                let color = colors[index].clone();
+
+                self.send_announcement(&message, color).await?;
+                ActionOutcome::Posted { message }
+            }
+            Action::SubHype(properties) => {
+                let message = non_empty(properties.message.as_deref())
+                    .map(|value| self.expand_template(value))
+                    .unwrap_or_else(|| DEFAULT_SUB_HYPE_MESSAGE.to_string());
+                let mode = properties.mode.unwrap_or_default();
+                let duration = Duration::from_secs(properties.duration.unwrap_or(30));
+
+                self.send_announcement(&message, AnnouncementColor::Purple)
+                    .await?;
+
+                // Mode was already enabled, nothing to restore
+                if self.set_chat_mode(mode, true).await? {
+                    return Ok(ActionOutcome::Posted { message });
+                }
+
+                let state = self.clone();
+                let generation = self.settings_generation();
+
+                spawn_local(async move {
+                    sleep(duration).await;
+
+                    if !state.is_settings_generation_current(generation) {
+                        tracing::info!("chat settings changed, skipping sub hype restore");
+                        return;
+                    }
+
+                    match state.set_chat_mode(mode, false).await {
+                        Ok(_) => tracing::info!(?mode, "restored chat mode after sub hype"),
+                        Err(error) => tracing::error!(?error, "failed to restore chat mode"),
+                    }
+                });
+
+                ActionOutcome::Posted { message }
+            }
+            Action::ToggleRewardAutoFulfill(properties) => {
+                let reward_id = non_empty(properties.reward_id.as_deref())
+                    .map(RewardId::from)
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing reward".into()))?;
+
+                let enabled = self.toggle_reward_auto_fulfill(reward_id).await?;
+                ActionOutcome::Toggled { enabled }
+            }
+            Action::ClipAndEdit => {
+                let clip = self
+                    .create_clip()
+                    .await?
+                    .into_iter()
+                    .next()
+                    .ok_or(TwitchError::NothingToDo("clip creation returned no clip"))?;
+
+                ActionOutcome::OpenUrl { url: clip.edit_url }
+            }
+            Action::ToggleMature => {
+                let enabled = self
+                    .toggle_content_classification(ContentClassificationId::MatureGame)
+                    .await?;
+                ActionOutcome::Toggled { enabled }
+            }
+            Action::PostFollowersGained => {
+                let gained = self.post_followers_gained().await?;
+                ActionOutcome::Count {
+                    count: gained as usize,
+                }
+            }
+            Action::PostSocials => {
+                self.post_socials().await?;
+                ActionOutcome::Completed
+            }
+            Action::LurkMode(properties) => {
+                let message = properties
+                    .message
+                    .unwrap_or_else(|| "Thanks for lurking!".to_string());
+                let message = self.expand_template(&message);
+
+                let enabled = self.toggle_lurk_mode(&message).await?;
+                ActionOutcome::Toggled { enabled }
+            }
+            Action::ClipAndPost(properties) => {
+                let delay = Duration::from_secs(properties.delay.unwrap_or(5));
+                let message = properties
+                    .message
+                    .unwrap_or_else(|| "Check out this clip! {url}".to_string());
+
+                let clip = self.clip_and_post(delay, message).await?;
+                ActionOutcome::Clip {
+                    id: clip.id.to_string(),
+                    edit_url: clip.edit_url,
+                }
+            }
+            Action::SetDatedTitle(properties) => {
+                let template = properties
+                    .template
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing template".into()))?;
+                let date_format = properties
+                    .date_format
+                    .unwrap_or_else(|| DEFAULT_DATE_FORMAT.to_string());
+
+                self.set_dated_title(&template, &date_format).await?;
+                ActionOutcome::Completed
+            }
+            Action::FulfillRedemptions(properties) => {
+                let reward_id = properties
+                    .reward_id
+                    .map(RewardId::from)
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing reward".into()))?;
+                let status = properties.status.unwrap_or_default();
+                let count = properties.count.unwrap_or(1);
+
+                let updated = self
+                    .update_redemptions(reward_id, status.into(), count)
+                    .await?;
+                ActionOutcome::Count { count: updated }
+            }
+            Action::MarkerCategory(properties) => {
+                if properties.labels.is_empty() {
+                    self.set_marker_category(None);
+                    return Ok(ActionOutcome::Completed);
+                }
+
+                let index = self.next_cycle_index(cycle_key, properties.labels.len());
+                let label = properties.labels[index].clone();
+                self.set_marker_category(Some(label.clone()));
+
+                ActionOutcome::Selected { value: label }
+            }
+            Action::PostStandings(properties) => {
+                let template =
+                    non_empty(properties.template.as_deref()).unwrap_or(DEFAULT_STANDINGS_TEMPLATE);
+
+                if !self.post_interaction_standings(template).await? {
+                    return Err(TwitchError::NothingToDo(
+                        "no active prediction or poll to post",
+                    ));
+                }
+
+                ActionOutcome::Completed
+            }
+            Action::PostChatStatus(properties) => {
+                let template = non_empty(properties.template.as_deref())
+                    .unwrap_or(DEFAULT_CHAT_STATUS_TEMPLATE);
+
+                if !self
+                    .post_chat_status(template, properties.post_when_clear)
+                    .await?
+                {
+                    return Err(TwitchError::NothingToDo("no chat restrictions are active"));
+                }
+
+                ActionOutcome::Completed
+            }
+            Action::PostRaidThanks(properties) => {
+                let template = non_empty(properties.template.as_deref())
+                    .unwrap_or(DEFAULT_RAID_THANKS_TEMPLATE);
+
+                if !self.post_raid_thanks(template).await? {
+                    return Err(TwitchError::NothingToDo("no recent raid to thank"));
+                }
+
+                ActionOutcome::Completed
+            }
+            Action::ExportMarkers(properties) => {
+                let path = non_empty(properties.path.as_deref())
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing export path".into()))?;
+
+                let count = self
+                    .export_markers(Path::new(path), properties.format)
+                    .await?;
+
+                if count == 0 {
+                    return Err(TwitchError::NothingToDo("no markers to export"));
+                }
+
+                ActionOutcome::Count { count }
+            }
+            Action::CycleChatDelay(properties) => {
+                if properties.delays.is_empty() {
+                    return Err(TwitchError::InvalidProperties("no chat delays".into()));
+                }
+
+                if let Some(delay) = properties
+                    .delays
+                    .iter()
+                    .find(|delay| **delay != 0 && !ALLOWED_CHAT_DELAYS.contains(delay))
+                {
+                    return Err(TwitchError::InvalidProperties(format!(
+                        "chat delay must be 0, 2, 4 or 6 seconds (got {delay})"
+                    )));
+                }
+
+                let index = self.next_cycle_index(cycle_key, properties.delays.len());
+                let delay = properties.delays[index];
+
+                self.set_chat_delay(delay).await?;
+                ActionOutcome::Selected {
+                    value: match delay {
+                        0 => "no chat delay".to_string(),
+                        delay => format!("{delay}s chat delay"),
+                    },
+                }
+            }
+            Action::MarkedMessage(properties) => {
+                let message = match non_empty(properties.message.as_deref()) {
+                    Some(value) => self.expand_template(value),
+                    None => return Err(TwitchError::InvalidProperties("missing message".into())),
+                };
+
+                self.send_marked_message(&message).await?;
+                ActionOutcome::Posted { message }
+            }
+            Action::ClearChatWithWarning(properties) => {
+                let delay = properties.delay.unwrap_or(3).min(MAX_CLEAR_CHAT_DELAY);
+                let template =
+                    non_empty(properties.message.as_deref()).unwrap_or(DEFAULT_CLEAR_CHAT_WARNING);
+
+                if !self.begin_pending_clear_chat() {
+                    return Err(TwitchError::NothingToDo("chat clear already pending"));
+                }
+
+                let mut values = self.template_values();
+                values.push(("seconds", delay.to_string()));
+                let message = expand_template(template, &values);
+
+                if let Err(error) = self
+                    .send_announcement(&message, AnnouncementColor::Primary)
+                    .await
+                {
+                    self.end_pending_clear_chat();
+                    return Err(error.into());
+                }
+
+                let state = self.clone();
+                spawn_local(async move {
+                    sleep(Duration::from_secs(delay)).await;
+
+                    match state.clear_chat().await {
+                        Ok(_) => tracing::info!("cleared chat after warning"),
+                        Err(error) => tracing::error!(?error, "failed to clear chat after warning"),
+                    }
+
+                    state.end_pending_clear_chat();
+                });
+
+                ActionOutcome::Posted { message }
+            }
+            Action::PostRandomClip(properties) => {
+                let template = non_empty(properties.template.as_deref())
+                    .unwrap_or(DEFAULT_RANDOM_CLIP_TEMPLATE);
+
+                if !self.post_random_clip(template).await? {
+                    return Err(TwitchError::NothingToDo("no recent clips to post"));
+                }
+
+                ActionOutcome::Completed
+            }
+            Action::FocusMode(properties) => {
+                let enter_message = non_empty(properties.enter_message.as_deref())
+                    .map(|value| self.expand_template(value))
+                    .unwrap_or_else(|| DEFAULT_FOCUS_ENTER_MESSAGE.to_string());
+                let exit_message = non_empty(properties.exit_message.as_deref())
+                    .map(|value| self.expand_template(value))
+                    .unwrap_or_else(|| DEFAULT_FOCUS_EXIT_MESSAGE.to_string());
+
+                let enabled = self
+                    .toggle_focus_mode(&enter_message, &exit_message)
+                    .await?;
+                ActionOutcome::Toggled { enabled }
+            }
+            Action::TempVip(properties) => {
+                let login = non_empty(properties.target_login.as_deref())
+                    .map(|value| value.trim_start_matches('@'))
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing target login".into()))?;
+                let minutes = properties.minutes.unwrap_or(60).max(1);
+
+                self.grant_temp_vip(login, Duration::from_secs(minutes * 60))
+                    .await?;
+
+                tracing::info!(%login, minutes, "granted temporary VIP");
+                ActionOutcome::Toggled { enabled: true }
+            }
+            Action::MarkViewerCount => {
+                let marker = self
+                    .create_viewer_count_marker()
+                    .await?
+                    .ok_or(TwitchError::NothingToDo("stream is offline"))?;
+
+                ActionOutcome::Marker {
+                    id: marker.id.to_string(),
+                }
+            }
+            Action::PostNextStream(properties) => {
+                let template = non_empty(properties.template.as_deref())
+                    .unwrap_or(DEFAULT_NEXT_STREAM_TEMPLATE);
+
+                if !self.post_next_stream(template).await? {
+                    return Err(TwitchError::NothingToDo("no upcoming scheduled stream"));
+                }
+
+                ActionOutcome::Completed
+            }
+            Action::RefreshAll => {
+                self.refresh_all().await;
+                ActionOutcome::Completed
+            }
+            Action::Timeout(properties) => {
+                let username = non_empty(properties.username.as_deref())
+                    .map(|value| value.trim_start_matches('@'))
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing username".into()))?;
+                let duration = properties.duration.unwrap_or(600);
+
+                self.timeout_user(username, duration, non_empty(properties.reason.as_deref()))
+                    .await?;
+
+                tracing::info!(%username, duration, "timed out user");
+                ActionOutcome::Completed
+            }
+            Action::Ban(properties) => {
+                // Nobody to ban, treated as a no-op
+                let username = match non_empty(properties.username.as_deref()) {
+                    Some(value) => value.trim_start_matches('@'),
+                    None => {
+                        tracing::debug!("ban action has no username, skipping");
+                        return Ok(ActionOutcome::Completed);
+                    }
+                };
+
+                self.ban_user(username, non_empty(properties.reason.as_deref()))
+                    .await?;
+
+                tracing::info!(%username, "banned user");
+                ActionOutcome::Completed
+            }
+            Action::Announcement(properties) => {
+                let message = match non_empty(properties.message.as_deref()) {
+                    Some(value) => self.expand_template(value),
+                    None => return Err(TwitchError::InvalidProperties("missing message".into())),
+                };
+
+                let color = properties.color.unwrap_or_default();
+                let color = parse_announcement_color(&color).unwrap_or_else(|error| {
+                    tracing::warn!(?error, "falling back to primary announcement color");
+                    AnnouncementColor::Primary
+                });
+
+                self.send_announcement(&message, color).await?;
+                ActionOutcome::Posted { message }
+            }
+            Action::StartRaid(properties) => {
+                let target = non_empty(properties.target_login.as_deref())
+                    .map(|value| value.trim_start_matches('@'))
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing target".into()))?;
+
+                self.start_raid(target).await?;
+
+                tracing::info!(%target, "started raid");
+                ActionOutcome::Completed
+            }
+            Action::CancelRaid => {
+                self.cancel_raid().await?;
+                ActionOutcome::Completed
+            }
+            Action::Shoutout(properties) => {
+                let target = non_empty(properties.target_login.as_deref())
+                    .map(|value| value.trim_start_matches('@'))
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing target".into()))?;
+
+                self.send_shoutout(target).await?;
+
+                tracing::info!(%target, "sent shoutout");
+                ActionOutcome::Completed
+            }
+            Action::UpdateCategory(properties) => {
+                let name = non_empty(properties.game_name.as_deref())
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing game name".into()))?;
+
+                self.set_stream_category(name).await?;
+
+                tracing::info!(%name, "updated stream category");
+                ActionOutcome::Completed
+            }
+            Action::CreatePoll(properties) => {
+                let title = non_empty(properties.title.as_deref())
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing title".into()))?;
+
+                let choices: Vec<String> = properties
+                    .choices
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|choice| !choice.trim().is_empty())
+                    .collect();
+                if !POLL_CHOICES_RANGE.contains(&choices.len()) {
+                    return Err(TwitchError::InvalidProperties(format!(
+                        "poll needs between {} and {} choices, got {}",
+                        POLL_CHOICES_RANGE.start(),
+                        POLL_CHOICES_RANGE.end(),
+                        choices.len()
+                    )));
+                }
+
+                let duration = properties.duration.unwrap_or(DEFAULT_POLL_DURATION);
+                if !POLL_DURATION_RANGE.contains(&duration) {
+                    return Err(TwitchError::InvalidProperties(format!(
+                        "poll duration must be between {} and {} seconds, got {duration}",
+                        POLL_DURATION_RANGE.start(),
+                        POLL_DURATION_RANGE.end(),
+                    )));
+                }
+
+                let title = self.expand_template(title);
+                let id = self.create_poll(&title, &choices, duration).await?;
+
+                tracing::info!(%id, %title, "created poll");
+                ActionOutcome::Completed
+            }
+            Action::EndPoll(properties) => {
+                let status = match properties.status.as_deref().map(str::to_ascii_uppercase) {
+                    None => PollStatus::Terminated,
+                    Some(value) if value == "TERMINATED" => PollStatus::Terminated,
+                    Some(value) if value == "ARCHIVED" => PollStatus::Archived,
+                    Some(value) => {
+                        return Err(TwitchError::InvalidProperties(format!(
+                            "invalid poll status \"{value}\""
+                        )));
+                    }
+                };
+
+                if !self.end_poll(status).await? {
+                    return Err(TwitchError::NothingToDo("no active poll"));
+                }
+
+                ActionOutcome::Completed
+            }
+            Action::CreatePrediction(properties) => {
+                let title = non_empty(properties.title.as_deref())
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing title".into()))?;
+
+                let outcomes: Vec<String> = properties
+                    .outcomes
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|outcome| !outcome.trim().is_empty())
+                    .collect();
+                if !PREDICTION_OUTCOMES_RANGE.contains(&outcomes.len()) {
+                    return Err(TwitchError::InvalidProperties(format!(
+                        "prediction needs between {} and {} outcomes, got {}",
+                        PREDICTION_OUTCOMES_RANGE.start(),
+                        PREDICTION_OUTCOMES_RANGE.end(),
+                        outcomes.len()
+                    )));
+                }
+
+                let window = properties.window.unwrap_or(DEFAULT_PREDICTION_WINDOW);
+                if !PREDICTION_WINDOW_RANGE.contains(&window) {
+                    return Err(TwitchError::InvalidProperties(format!(
+                        "prediction window must be between {} and {} seconds, got {window}",
+                        PREDICTION_WINDOW_RANGE.start(),
+                        PREDICTION_WINDOW_RANGE.end(),
+                    )));
+                }
+
+                let title = self.expand_template(title);
+                let id = self.create_prediction(&title, &outcomes, window).await?;
+
+                tracing::info!(%id, %title, "created prediction");
+                ActionOutcome::Completed
+            }
+            Action::ResolvePrediction(properties) => {
+                let status = match properties.status.as_deref().map(str::to_ascii_uppercase) {
+                    Some(value) if value == "LOCKED" => PredictionStatus::Locked,
+                    Some(value) if value == "RESOLVED" => PredictionStatus::Resolved,
+                    Some(value) if value == "CANCELED" => PredictionStatus::Canceled,
+                    Some(value) => {
+                        return Err(TwitchError::InvalidProperties(format!(
+                            "invalid prediction status \"{value}\""
+                        )));
+                    }
+                    None => return Err(TwitchError::InvalidProperties("missing status".into())),
+                };
+
+                if matches!(status, PredictionStatus::Resolved)
+                    && properties.winning_outcome_index.is_none()
+                {
+                    return Err(TwitchError::InvalidProperties(
+                        "missing winning outcome".into(),
+                    ));
+                }
+
+                if !self
+                    .resolve_prediction(status, properties.winning_outcome_index)
+                    .await?
+                {
+                    return Err(TwitchError::NothingToDo("no active prediction"));
+                }
+
+                ActionOutcome::Completed
+            }
+            Action::AddVip(properties) => {
+                let username = non_empty(properties.username.as_deref())
+                    .map(|value| value.trim_start_matches('@'))
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing username".into()))?;
+
+                let user_id = self.get_user_id_by_login(username).await?;
+                self.add_vip(user_id).await?;
+
+                tracing::info!(%username, "added VIP");
+                ActionOutcome::Completed
+            }
+            Action::RemoveVip(properties) => {
+                let username = non_empty(properties.username.as_deref())
+                    .map(|value| value.trim_start_matches('@'))
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing username".into()))?;
+
+                let user_id = self.get_user_id_by_login(username).await?;
+                self.remove_vip(user_id).await?;
+
+                tracing::info!(%username, "removed VIP");
+                ActionOutcome::Completed
+            }
+            Action::AddModerator(properties) => {
+                let username = non_empty(properties.username.as_deref())
+                    .map(|value| value.trim_start_matches('@'))
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing username".into()))?;
+
+                self.add_moderator(username).await?;
+
+                tracing::info!(%username, "added moderator");
+                ActionOutcome::Completed
+            }
+            Action::RemoveModerator(properties) => {
+                let username = non_empty(properties.username.as_deref())
+                    .map(|value| value.trim_start_matches('@'))
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing username".into()))?;
+
+                self.remove_moderator(username).await?;
+
+                tracing::info!(%username, "removed moderator");
+                ActionOutcome::Completed
+            }
+            Action::WarnUser(properties) => {
+                let username = non_empty(properties.username.as_deref())
+                    .map(|value| value.trim_start_matches('@'))
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing username".into()))?;
+                let reason = non_empty(properties.reason.as_deref())
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing reason".into()))?;
+
+                self.warn_user(username, reason).await?;
+
+                tracing::info!(%username, "warned user");
+                ActionOutcome::Completed
+            }
+            Action::SnoozeAd => {
+                let snooze = self.snooze_next_ad().await?;
+
+                tracing::info!(
+                    snoozes_left = snooze.snooze_count,
+                    next_ad_at = %snooze.next_ad_at,
+                    "snoozed next ad"
+                );
+                ActionOutcome::Count {
+                    count: snooze.snooze_count as usize,
+                }
+            }
+            Action::DeleteMessage(properties) => {
+                // Without a message id twitch would clear the whole chat
+                let message_id = non_empty(properties.message_id.as_deref())
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing message id".into()))?;
+
+                self.delete_message(message_id).await?;
+
+                tracing::info!(%message_id, "deleted chat message");
+                ActionOutcome::Completed
+            }
+            Action::BlockUser(properties) => {
+                let username = non_empty(properties.username.as_deref())
+                    .map(|value| value.trim_start_matches('@'))
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing username".into()))?;
+
+                self.block_user(username).await?;
+
+                tracing::info!(%username, "blocked user");
+                ActionOutcome::Completed
+            }
+            Action::UnblockUser(properties) => {
+                let username = non_empty(properties.username.as_deref())
+                    .map(|value| value.trim_start_matches('@'))
+                    .ok_or_else(|| TwitchError::InvalidProperties("missing username".into()))?;
+
+                self.unblock_user(username).await?;
+
+                tracing::info!(%username, "unblocked user");
+                ActionOutcome::Completed
+            }
+            Action::SetSlowMode(properties) => {
+                let enabled = properties.enabled.unwrap_or(true);
+                self.set_slow_mode(enabled, properties.wait_seconds.map(u64::from))
+                    .await?;
+
+                ActionOutcome::Toggled { enabled }
+            }
+            Action::SetFollowerMode(properties) => {
+                let enabled = properties.enabled.unwrap_or(true);
+                self.set_follower_mode(enabled, properties.duration_minutes.map(u64::from))
+                    .await?;
+
+                ActionOutcome::Toggled { enabled }
+            }
+            Action::UniqueChat => {
+                self.toggle_unique_chat().await?;
+                ActionOutcome::Completed
+            }
+            Action::Lockdown => {
+                self.set_lockdown(true).await?;
+                ActionOutcome::Toggled { enabled: true }
+            }
+            Action::Unlock => {
+                self.set_lockdown(false).await?;
+                ActionOutcome::Toggled { enabled: false }
+            }
+        };
+
+        Ok(outcome)
+    }
+}
+
+/// Treats empty strings from the inspector the same as missing values
+pub fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.filter(|value| !value.trim().is_empty())
+}
@@ -0,0 +1,241 @@
+use twitch_api::types::TimestampRef;
+
+/// Formats a count for display on a tile, values of a thousand or more
+/// are abbreviated (e.g 1234 -> "1.2K", 2500000 -> "2.5M")
+pub fn format_count(count: i64) -> String {
+    let abs = count.unsigned_abs();
+
+    let (value, suffix) = match abs {
+        0..1_000 => return count.to_string(),
+        1_000..1_000_000 => (abs as f64 / 1_000.0, "K"),
+        _ => (abs as f64 / 1_000_000.0, "M"),
+    };
+
+    let sign = if count < 0 { "-" } else { "" };
+    let value = format!("{value:.1}");
+    let value = value.strip_suffix(".0").unwrap_or(&value);
+
+    format!("{sign}{value}{suffix}")
+}
+
+/// Formats the date portion of a timestamp, `YYYY`, `MM` and `DD` within
+/// the format are replaced with the year, month and day (e.g "YYYY-MM-DD")
+pub fn format_date(timestamp: &TimestampRef, format: &str) -> String {
+    format
+        .replace("YYYY", timestamp.year())
+        .replace("MM", timestamp.month())
+        .replace("DD", timestamp.day())
+}
+
+/// Splits the message into chunks of at most `limit` characters, characters
+/// are counted as unicode code points so multibyte characters are never split
+pub fn chunk_chars(message: &str, limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut chars = message.chars();
+
+    loop {
+        let chunk: String = chars.by_ref().take(limit).collect();
+        if chunk.is_empty() {
+            break;
+        }
+
+        chunks.push(chunk);
+    }
+
+    chunks
+}
+
+/// Joins the parts using the separator, splitting them into multiple chunks
+/// so that no chunk exceeds `limit` characters. Parts are never split across
+/// chunks unless a single part is longer than the limit by itself
+pub fn join_chunked(parts: &[String], separator: &str, limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_length = 0;
+    let separator_length = separator.chars().count();
+
+    for part in parts {
+        let part_length = part.chars().count();
+
+        if current_length > 0 && current_length + separator_length + part_length > limit {
+            chunks.push(std::mem::take(&mut current));
+            current_length = 0;
+        }
+
+        if current_length > 0 {
+            current.push_str(separator);
+            current_length += separator_length;
+        }
+
+        current.push_str(part);
+        current_length += part_length;
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+}
+
+/// Formats the share of the total each option holds as a percentage
+/// (e.g "Blue: 60% | Pink: 40%")
+pub fn format_standings(options: &[(&str, i64)]) -> String {
+    let total: i64 = options.iter().map(|(_, value)| (*value).max(0)).sum();
+
+    options
+        .iter()
+        .map(|(title, value)| {
+            let percent = if total > 0 {
+                ((*value).max(0) as f64 / total as f64 * 100.0).round() as i64
+            } else {
+                0
+            };
+
+            format!("{title}: {percent}%")
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Converts a timestamp into seconds since the unix epoch, returns [None]
+/// when the timestamp components are not valid numbers
+pub fn timestamp_seconds(timestamp: &TimestampRef) -> Option<i64> {
+    let year: i64 = timestamp.year().parse().ok()?;
+    let month: i64 = timestamp.month().parse().ok()?;
+    let day: i64 = timestamp.day().parse().ok()?;
+    let hour: i64 = timestamp.hour().parse().ok()?;
+    let minute: i64 = timestamp.minute().parse().ok()?;
+    let second: i64 = timestamp.second().parse().ok()?;
+
+    // Days from civil date (proleptic gregorian calendar)
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    Some(days * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+/// Seconds elapsed between the stream start and now (both seconds since the
+/// unix epoch), clamped to zero when the clocks disagree
+pub fn uptime_seconds(started_at: i64, now: i64) -> u64 {
+    (now - started_at).max(0) as u64
+}
+
+/// Formats seconds since the unix epoch as an RFC3339 timestamp
+/// (e.g "2025-01-31T12:00:00Z"), the inverse of [timestamp_seconds]
+pub fn format_timestamp(seconds: i64) -> String {
+    let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    // Civil date from days (proleptic gregorian calendar)
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3600,
+        (time % 3600) / 60,
+        time % 60
+    )
+}
+
+/// Formats a number of seconds as a duration (e.g 3725 -> "1:02:05", 65 -> "01:05")
+pub fn format_duration(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let (hours, minutes, seconds) = (seconds / 3600, (seconds % 3600) / 60, seconds % 60);
+
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    }
+}
+
+/// Formats a number of seconds as a compact duration
+/// (e.g 8100 -> "2h15m", 300 -> "5m", 97200 -> "1d3h")
+pub fn format_compact_duration(seconds: i64) -> String {
+    let minutes = seconds.max(0) / 60;
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    let (days, hours) = (hours / 24, hours % 24);
+
+    if days > 0 {
+        format!("{days}d{hours}h")
+    } else if hours > 0 {
+        format!("{hours}h{minutes:02}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Chunks of emoji text never exceed the limit and keep every character
+    #[test]
+    fn chunk_chars_emoji() {
+        let message = "😀".repeat(1234);
+        let chunks = chunk_chars(&message, 500);
+
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 500));
+        assert_eq!(chunks.concat(), message);
+    }
+
+    /// Chunks of CJK text never exceed the limit and keep every character
+    #[test]
+    fn chunk_chars_cjk() {
+        let message = "你好世界".repeat(300);
+        let chunks = chunk_chars(&message, 500);
+
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 500));
+        assert_eq!(chunks.concat(), message);
+    }
+
+    /// Multibyte messages under the limit are sent as a single chunk even
+    /// though their byte length exceeds it
+    #[test]
+    fn chunk_chars_multibyte_single_chunk() {
+        let message = "界".repeat(499);
+        assert!(message.len() > 500);
+
+        let chunks = chunk_chars(&message, 500);
+        assert_eq!(chunks, vec![message]);
+    }
+
+    /// Uptime is the seconds elapsed since a fixed stream start
+    #[test]
+    fn uptime_from_fixed_start() {
+        let started_at = TimestampRef::from_static("2024-03-01T12:00:00Z");
+        let started_at = timestamp_seconds(started_at).unwrap();
+        assert_eq!(started_at, 1_709_294_400);
+
+        let now = TimestampRef::from_static("2024-03-01T14:15:30Z");
+        let now = timestamp_seconds(now).unwrap();
+        assert_eq!(uptime_seconds(started_at, now), 8130);
+    }
+
+    /// Uptime is clamped to zero when the local clock is behind twitch
+    #[test]
+    fn uptime_clock_skew() {
+        let started_at = TimestampRef::from_static("2024-03-01T12:00:00Z");
+        let started_at = timestamp_seconds(started_at).unwrap();
+        assert_eq!(uptime_seconds(started_at, started_at - 5), 0);
+    }
+}
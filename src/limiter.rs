@@ -0,0 +1,97 @@
+use std::cell::Cell;
+
+use tilepad_plugin_sdk::tracing;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Default number of actions allowed to execute at once
+pub const DEFAULT_MAX_CONCURRENT_ACTIONS: usize = 4;
+
+/// Limits the number of actions talking to twitch at once, excess actions
+/// wait for a running action to finish to avoid bursts of requests
+pub struct ActionLimiter {
+    semaphore: Semaphore,
+    limit: Cell<usize>,
+    /// Permits held by running actions that must be forgotten once
+    /// returned to apply a lowered limit
+    pending_reduction: Cell<usize>,
+}
+
+impl Default for ActionLimiter {
+    fn default() -> Self {
+        Self {
+            semaphore: Semaphore::new(DEFAULT_MAX_CONCURRENT_ACTIONS),
+            limit: Cell::new(DEFAULT_MAX_CONCURRENT_ACTIONS),
+            pending_reduction: Cell::new(0),
+        }
+    }
+}
+
+impl ActionLimiter {
+    /// Updates the number of actions allowed to execute at once. Lowering the
+    /// limit below the number of running actions only takes effect as the
+    /// running actions finish
+    pub fn set_limit(&self, limit: usize) {
+        let limit = limit.max(1);
+        let current = self.limit.replace(limit);
+
+        if limit > current {
+            // Permits waiting to be forgotten are kept instead of adding new ones
+            let increase = limit - current;
+            let pending = self.pending_reduction.get();
+            let kept = pending.min(increase);
+
+            self.pending_reduction.set(pending - kept);
+            self.semaphore.add_permits(increase - kept);
+        } else if limit < current {
+            let reduction = current - limit;
+            let forgotten = self.semaphore.forget_permits(reduction);
+
+            // Permits held by running actions cannot be forgotten yet, they
+            // are forgotten when the running actions release them
+            self.pending_reduction
+                .set(self.pending_reduction.get() + reduction - forgotten);
+        }
+    }
+
+    /// Waits for a slot to execute an action, the slot is released when
+    /// the returned [ActionSlot] is dropped
+    pub async fn acquire(&self) -> Option<ActionSlot<'_>> {
+        let permit = match self.semaphore.try_acquire() {
+            Ok(permit) => permit,
+            Err(_) => {
+                tracing::info!(
+                    limit = self.limit.get(),
+                    "too many actions running, action queued"
+                );
+                self.semaphore.acquire().await.ok()?
+            }
+        };
+
+        Some(ActionSlot {
+            limiter: self,
+            permit: Some(permit),
+        })
+    }
+}
+
+/// Slot held by a running action, see [ActionLimiter::acquire]
+pub struct ActionSlot<'a> {
+    limiter: &'a ActionLimiter,
+    permit: Option<SemaphorePermit<'a>>,
+}
+
+impl Drop for ActionSlot<'_> {
+    fn drop(&mut self) {
+        let Some(permit) = self.permit.take() else {
+            return;
+        };
+
+        // Limit was lowered while the action was running, the permit is
+        // forgotten rather than returned to apply the new limit
+        let pending = self.limiter.pending_reduction.get();
+        if pending > 0 {
+            self.limiter.pending_reduction.set(pending - 1);
+            permit.forget();
+        }
+    }
+}
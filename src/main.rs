@@ -3,9 +3,20 @@ use tilepad_plugin_sdk::{setup_tracing, start_plugin};
 use tokio::task::LocalSet;
 
 pub mod action;
+pub mod color;
+pub mod displays;
+pub mod error;
+pub mod eventsub;
+pub mod execute;
+pub mod format;
+pub mod limiter;
+pub mod marker_log;
 pub mod messages;
 pub mod plugin;
+pub mod properties;
+pub mod ratelimit;
 pub mod state;
+pub mod template;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
@@ -0,0 +1,129 @@
+use std::path::Path;
+
+use serde::Deserialize;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+use twitch_api::types::Timestamp;
+
+use crate::format::format_duration;
+
+/// Length of the final chapter when the stream end is unknown (seconds)
+const DEFAULT_FINAL_CHAPTER_LENGTH: i64 = 60;
+
+/// File the marker log is appended to, relative to the plugin directory
+pub const MARKER_LOG_FILE: &str = "markers.log";
+
+/// Entry in the local log of markers created during the session
+#[derive(Debug, Clone)]
+pub struct MarkerLogEntry {
+    /// Label shared by the marker and clip
+    pub label: String,
+    /// Time the entry was captured
+    pub created_at: Timestamp,
+    /// Position of the marker in the stream (seconds)
+    pub position_seconds: Option<i64>,
+    /// URL to edit the clip captured alongside the marker
+    pub clip_url: Option<String>,
+}
+
+impl MarkerLogEntry {
+    /// Formats the entry as a single tab separated line
+    pub fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\n",
+            self.created_at.as_str(),
+            self.position_seconds
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            self.label,
+            self.clip_url.as_deref().unwrap_or_default(),
+        )
+    }
+}
+
+/// Appends the provided entry to the marker log file
+pub async fn append_marker_log(path: &Path, entry: &MarkerLogEntry) -> std::io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+
+    file.write_all(entry.to_line().as_bytes()).await?;
+    file.flush().await?;
+    Ok(())
+}
+
+/// File format markers can be exported as
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ChapterFormat {
+    /// Chapter list suitable for a YouTube description
+    #[default]
+    Youtube,
+    /// WebVTT chapters track
+    Webvtt,
+}
+
+/// Formats the chapters using the provided format, chapters are the start
+/// (seconds from stream start) and label of each chapter in order.
+///
+/// `end_seconds` is the end of the final chapter when known
+pub fn format_chapters(
+    chapters: &[(i64, &str)],
+    format: ChapterFormat,
+    end_seconds: Option<i64>,
+) -> String {
+    match format {
+        ChapterFormat::Youtube => {
+            let mut output = String::new();
+
+            // YouTube requires the first chapter to start at zero
+            if chapters.first().is_some_and(|(start, _)| *start > 0) {
+                output.push_str("00:00 Start\n");
+            }
+
+            for (start, label) in chapters {
+                output.push_str(&format!("{} {}\n", format_duration(*start), label));
+            }
+
+            output
+        }
+        ChapterFormat::Webvtt => {
+            let mut output = String::from("WEBVTT\n");
+
+            for (index, (start, label)) in chapters.iter().enumerate() {
+                let end = chapters
+                    .get(index + 1)
+                    .map(|(next, _)| *next)
+                    .or(end_seconds)
+                    .filter(|end| end > start)
+                    .unwrap_or(start + DEFAULT_FINAL_CHAPTER_LENGTH);
+
+                output.push_str(&format!(
+                    "\n{} --> {}\n{}\n",
+                    format_cue_time(*start),
+                    format_cue_time(end),
+                    label
+                ));
+            }
+
+            output
+        }
+    }
+}
+
+/// Formats seconds as a WebVTT cue timestamp (e.g "01:02:05.000")
+fn format_cue_time(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    format!(
+        "{:02}:{:02}:{:02}.000",
+        seconds / 3600,
+        (seconds % 3600) / 60,
+        seconds % 60
+    )
+}
+
+/// Writes exported chapters to the provided path, replacing any existing file
+pub async fn write_chapters(path: &Path, contents: &str) -> std::io::Result<()> {
+    tokio::fs::write(path, contents).await
+}
@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use twitch_api::helix::Scope;
+
+use crate::properties::{RaidDefense, SocialLink, TitleOverflow};
 
 /// Messages from the inspector
 #[derive(Debug, Deserialize, Serialize)]
@@ -7,25 +10,289 @@ pub enum InspectorMessageIn {
     GetState,
     OpenAuthUrl,
     Logout,
+    /// Authorize a separate bot account for sending chat messages
+    OpenBotAuthUrl,
+    BotLogout,
+    SetIdle {
+        idle: bool,
+    },
+    GetSocials,
+    SetSocials {
+        socials: Vec<SocialLink>,
+    },
+    PreviewMessage {
+        template: String,
+    },
+    SetTitleOverflow {
+        overflow: TitleOverflow,
+    },
+    SetAutoRaidDefense {
+        enabled: bool,
+        #[serde(default)]
+        defense: RaidDefense,
+    },
+    SetPollInterval {
+        secs: Option<u64>,
+    },
+    /// Runs an action as if a tile was pressed, used for testing actions
+    DispatchAction {
+        action_id: String,
+        #[serde(default)]
+        properties: serde_json::Value,
+    },
+    GetRewards {
+        /// Bypass the cached rewards list
+        #[serde(default)]
+        refresh: bool,
+    },
+    /// Runs read only checks to verify the plugin is able to reach twitch
+    SelfTest,
+    /// Clears cached state and fetches it again from twitch
+    RefreshAll,
 }
 
 /// Messages to the inspector
 #[derive(Serialize)]
 #[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum InspectorMessageOut {
-    State { state: String },
+    State {
+        state: String,
+    },
+    /// Login of the bot account sending chat messages, [None] when chat
+    /// is sent by the broadcaster
+    BotState {
+        login: Option<String>,
+    },
+    Idle {
+        idle: bool,
+    },
+    Socials {
+        socials: Vec<SocialLink>,
+    },
+    MessagePreview {
+        text: String,
+    },
+    TitleTooLong {
+        length: usize,
+        max: usize,
+    },
+    Rewards {
+        rewards: Vec<RewardSummary>,
+    },
+    /// Result of an action dispatched from the inspector
+    DispatchResult {
+        action_id: String,
+        success: bool,
+    },
+    /// Authentication was abandoned after twitch failed to respond in time
+    AuthTimeout,
+    /// Raid defense was applied for an incoming raid
+    RaidDefenseApplied {
+        raider: String,
+        viewers: i64,
+    },
+    /// Result of a single self test check
+    SelfTestStep {
+        step: String,
+        success: bool,
+        error: Option<String>,
+    },
+    /// Self test finished
+    SelfTestSummary {
+        passed: usize,
+        failed: usize,
+    },
+    /// Actions usable with the scopes granted to the plugin, actions not
+    /// included here will fail when used
+    AvailableActions {
+        actions: Vec<&'static str>,
+    },
+    /// Cached state was cleared and fetched again
+    RefreshComplete {
+        success: bool,
+    },
+    /// Scopes the stored access was not granted, the user must authorize
+    /// again to use the plugin. Empty when nothing is missing
+    MissingScopes {
+        scopes: Vec<Scope>,
+    },
+    /// Seconds until the access token expires, [None] when the token
+    /// does not expire
+    TokenExpiry {
+        expires_in_secs: Option<u64>,
+    },
+    /// Profile of the authenticated user
+    UserInfo {
+        login: String,
+        display_name: String,
+        profile_image_url: String,
+    },
+}
+
+/// Channel points reward that can be managed by the plugin
+#[derive(Debug, Clone, Serialize)]
+pub struct RewardSummary {
+    pub id: String,
+    pub title: String,
 }
 
 /// Messages from a display
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum DisplayMessageIn {
-    GetViewCount,
+    GetViewCount {
+        /// Interval in seconds the display would like the value refreshed at
+        #[serde(default)]
+        refresh_secs: Option<u64>,
+    },
+    GetViewCountTrend {
+        /// Interval in seconds the display would like the value refreshed at
+        #[serde(default)]
+        refresh_secs: Option<u64>,
+    },
+    GetGoalProgress {
+        /// Interval in seconds the display would like the value refreshed at
+        #[serde(default)]
+        refresh_secs: Option<u64>,
+    },
+    GetActiveDisplays,
+    GetProfileImage,
+    GetPendingRedemptions {
+        /// Interval in seconds the display would like the value refreshed at
+        #[serde(default)]
+        refresh_secs: Option<u64>,
+    },
+    GetClipsToday {
+        /// Interval in seconds the display would like the value refreshed at
+        #[serde(default)]
+        refresh_secs: Option<u64>,
+    },
+    GetApiLatency {
+        /// Interval in seconds the display would like the value refreshed at
+        #[serde(default)]
+        refresh_secs: Option<u64>,
+    },
+    GetActiveInteractions {
+        /// Interval in seconds the display would like the value refreshed at
+        #[serde(default)]
+        refresh_secs: Option<u64>,
+    },
+    GetCategoryViewers {
+        /// Interval in seconds the display would like the value refreshed at
+        #[serde(default)]
+        refresh_secs: Option<u64>,
+    },
+    GetFollowerCount {
+        /// Interval in seconds the display would like the value refreshed at
+        #[serde(default)]
+        refresh_secs: Option<u64>,
+    },
+    GetUptime {
+        /// Interval in seconds the display would like the value refreshed at
+        #[serde(default)]
+        refresh_secs: Option<u64>,
+    },
+    GetCategory {
+        /// Interval in seconds the display would like the value refreshed at
+        #[serde(default)]
+        refresh_secs: Option<u64>,
+    },
+    GetStreamTitle {
+        /// Interval in seconds the display would like the value refreshed at
+        #[serde(default)]
+        refresh_secs: Option<u64>,
+    },
 }
 
 /// Messages to a display
 #[derive(Serialize)]
 #[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum DisplayMessageOut {
-    ViewCount { count: usize },
+    ViewCount {
+        count: usize,
+    },
+    /// View count along with the change since the previous poll, the delta
+    /// is zero and the trend flat while offline
+    ViewCountTrend {
+        count: usize,
+        delta: i64,
+        trend: ViewCountTrend,
+    },
+    /// Progress of the current creator goal, both fields are
+    /// [None] when there is no active goal
+    GoalProgress {
+        percent: Option<u32>,
+        label: Option<String>,
+    },
+    /// Number of displays currently keeping polling active
+    ActiveDisplays {
+        count: usize,
+    },
+    /// Profile image of the authenticated user, [None] when unavailable
+    ProfileImage {
+        url: Option<String>,
+    },
+    /// Number of unfulfilled redemptions awaiting action
+    PendingRedemptions {
+        count: usize,
+    },
+    /// Number of clips created on the channel today
+    ClipsToday {
+        count: usize,
+    },
+    /// Round trip time of the latest twitch API request, [None] when
+    /// there is no recent measurement
+    ApiLatency {
+        millis: Option<u64>,
+    },
+    /// Number of polls and predictions awaiting an end or resolution
+    ActiveInteractions {
+        polls: usize,
+        predictions: usize,
+    },
+    /// Total viewers across the current category, [None] while offline
+    CategoryViewers {
+        total: Option<usize>,
+        your_share_percent: Option<f64>,
+    },
+    /// Total followers of the channel, [None] until first fetched
+    FollowerCount {
+        count: Option<u64>,
+    },
+    /// Seconds since the stream started, [None] while offline
+    Uptime {
+        seconds: Option<u64>,
+    },
+    /// Current category of the channel, [None] when no category is set
+    Category {
+        name: Option<String>,
+    },
+    /// Current title of the channel, [None] until first fetched
+    StreamTitle {
+        title: Option<String>,
+    },
+    /// Result of an action triggered by the tile the display belongs to
+    ActionResult {
+        success: bool,
+        message: String,
+    },
+}
+
+/// Direction the view count moved since the previous poll
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ViewCountTrend {
+    Up,
+    Down,
+    Flat,
+}
+
+impl ViewCountTrend {
+    pub fn from_delta(delta: i64) -> Self {
+        match delta.signum() {
+            1 => ViewCountTrend::Up,
+            -1 => ViewCountTrend::Down,
+            _ => ViewCountTrend::Flat,
+        }
+    }
 }
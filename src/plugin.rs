@@ -1,18 +1,28 @@
 use crate::{
-    action::Action,
-    messages::{DisplayMessageIn, DisplayMessageOut, InspectorMessageIn},
-    state::{State, run_view_count_update},
+    action::{ACTION_SCOPES, Action, ActionConditions},
+    displays::Metric,
+    error::{AuthError, TwitchError},
+    eventsub::run_eventsub,
+    execute::ActionOutcome,
+    messages::{
+        DisplayMessageIn, DisplayMessageOut, InspectorMessageIn, InspectorMessageOut,
+        ViewCountTrend,
+    },
+    properties::{Properties, StoredAccess},
+    state::{State, run_metrics_update, run_token_validation},
 };
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use std::rc::Rc;
 use tilepad_plugin_sdk::{
-    Inspector, Plugin, PluginSessionHandle, TileInteractionContext,
+    DeviceIndicator, Display, Inspector, Plugin, PluginSessionHandle, TileInteractionContext,
     tracing::{self},
 };
 use tokio::task::spawn_local;
 use twitch_api::{
     helix::Scope,
-    twitch_oauth2::{AccessToken, ImplicitUserTokenBuilder, types::ClientIdRef, url::Url},
+    twitch_oauth2::{
+        AccessToken, CsrfToken, ImplicitUserTokenBuilder, types::ClientIdRef, url::Url,
+    },
 };
 
 /// If you are forking this app program for your own use, please create your own
@@ -20,40 +30,105 @@ use twitch_api::{
 pub const TWITCH_CLIENT_ID: &ClientIdRef =
     ClientIdRef::from_static("yr9puvx670aq6m8beggiakivxob6tx");
 
-/// Scopes required from twitch by the app
-pub const TWITCH_REQUIRED_SCOPES: &[Scope] = &[
-    // Send chat messages
+/// Scopes required by features that are not tied to a specific action,
+/// scopes for actions are declared in [ACTION_SCOPES]
+pub const TWITCH_BACKGROUND_SCOPES: &[Scope] = &[
+    // Read creator goals for the goal display
+    Scope::ChannelReadGoals,
+    // Receive follow events and read the follower count
+    Scope::ModeratorReadFollowers,
+    // Read channel point rewards and pending redemptions
+    Scope::ChannelReadRedemptions,
+    // Read active polls and predictions for the interactions display
+    Scope::ChannelReadPolls,
+    Scope::ChannelReadPredictions,
+    // Receive subscription events
+    Scope::ChannelReadSubscriptions,
+    // Raid defense welcome message and chat mode
     Scope::UserWriteChat,
-    // Allow deleting messages
-    Scope::ModeratorManageChatMessages,
+    Scope::ModeratorManageChatSettings,
+    // Allow a separate bot account to chat in the channel
+    Scope::ChannelBot,
 ];
 
-/// Properties for the plugin itself
-#[derive(Debug, Deserialize, Serialize)]
-pub struct Properties {
-    access: Option<StoredAccess>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-struct StoredAccess {
-    access_token: AccessToken,
-    scopes: Vec<Scope>,
-}
+/// Scopes required for the bot account that sends chat messages
+pub const TWITCH_BOT_REQUIRED_SCOPES: &[Scope] = &[
+    // Send chat messages
+    Scope::UserWriteChat,
+    // Chat in the broadcasters channel as a bot
+    Scope::UserBot,
+];
 
 #[derive(Default)]
 pub struct TwitchPlugin {
     state: Rc<State>,
+    /// CSRF token of a pending bot account authorization, used to tell the
+    /// bot authorization apart from the broadcaster when the deep link arrives
+    bot_auth_csrf: Option<CsrfToken>,
+    /// Most recent display of each tile, used to report action results back
+    /// to the tile that triggered the action
+    tile_displays: Vec<Display>,
 }
 
 impl TwitchPlugin {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Stores the display replacing any previous display of the same tile
+    fn remember_tile_display(&mut self, display: &Display) {
+        self.tile_displays
+            .retain(|other| other.ctx.tile_id != display.ctx.tile_id);
+        self.tile_displays.push(display.clone());
+    }
+
+    /// Runs the action reporting the result through the reporter, `cycle_key`
+    /// identifies the source of the action for actions that cycle values
+    fn handle_action(
+        &self,
+        session: &PluginSessionHandle,
+        cycle_key: String,
+        action: Action,
+        conditions: Result<ActionConditions, TwitchError>,
+        reporter: ActionReporter,
+    ) {
+        let state = self.state.clone();
+        let session = session.clone();
+
+        reporter.report(DeviceIndicator::Loading);
+
+        spawn_local(async move {
+            let result = match conditions {
+                Ok(conditions) => state.execute_action(action, conditions, &cycle_key).await,
+                Err(error) => Err(error),
+            };
+
+            match result {
+                Ok(outcome) => {
+                    tracing::info!(action = reporter.action_id(), %outcome, "action completed");
+                    reporter.report_result(true, outcome.to_string());
+
+                    if let ActionOutcome::OpenUrl { url } = outcome {
+                        _ = session.open_url(url);
+                    }
+
+                    reporter.report(DeviceIndicator::Success);
+                }
+                Err(error) => {
+                    tracing::error!(action = reporter.action_id(), ?error, "action failed");
+                    reporter.report_result(false, error.to_string());
+                    reporter.report(DeviceIndicator::Error);
+                }
+            }
+        });
+    }
 }
 
 impl Plugin for TwitchPlugin {
-    fn on_registered(&mut self, _session: &PluginSessionHandle) {
-        spawn_local(run_view_count_update(self.state.clone()));
+    fn on_registered(&mut self, session: &PluginSessionHandle) {
+        spawn_local(run_metrics_update(self.state.clone()));
+        spawn_local(run_eventsub(self.state.clone()));
+        spawn_local(run_token_validation(self.state.clone(), session.clone()));
     }
 
     fn on_properties(&mut self, session: &PluginSessionHandle, properties: serde_json::Value) {
@@ -66,16 +141,68 @@ impl Plugin for TwitchPlugin {
             }
         };
 
+        let access = properties.access.clone();
+        let bot_access = properties.bot_access.clone();
+        state.set_properties(properties);
         state.set_logged_out();
+        state.set_bot_logged_out();
 
         let session = session.clone();
 
         // Try and authenticate
         spawn_local(async move {
-            if let Some(stored) = properties.access {
-                if let Err(error) = state.attempt_auth(stored.access_token).await {
-                    tracing::error!(?error, "auth attempt failed");
-                    _ = session.set_properties(Properties { access: None });
+            if let Some(stored) = bot_access {
+                match state.attempt_bot_auth(stored.access_token).await {
+                    Ok(_) => {}
+                    // Token is kept so the bot can be authorized again to grant the scopes
+                    Err(AuthError::MissingScopes(missing)) => {
+                        tracing::warn!(?missing, "bot access token is missing required scopes");
+                    }
+                    Err(error) => {
+                        tracing::error!(?error, "bot auth attempt failed");
+
+                        if error.is_invalid_token() {
+                            state.update_properties(&session, |properties| {
+                                properties.bot_access = None
+                            });
+                        }
+                    }
+                }
+            }
+
+            if let Some(stored) = access {
+                match state.attempt_auth(stored.access_token).await {
+                    Ok(_) => {}
+                    // Token is kept so the user can authorize again to grant the scopes
+                    Err(AuthError::MissingScopes(missing)) => {
+                        tracing::warn!(?missing, "access token is missing required scopes");
+                        state.set_missing_scopes(missing);
+                        return;
+                    }
+                    Err(error) => {
+                        tracing::error!(?error, "auth attempt failed");
+
+                        // Only forget the stored token when twitch rejected it
+                        if error.is_invalid_token() {
+                            state
+                                .update_properties(&session, |properties| properties.access = None);
+                        }
+                        return;
+                    }
+                }
+
+                // Scopes required by newer versions of the plugin have not been
+                // granted, the user must authorize again to grant them
+                let granted = state
+                    .get_user_token()
+                    .map(|token| token.scopes().to_vec())
+                    .unwrap_or_default();
+                let missing = missing_scopes(&required_scopes(), &granted);
+
+                if !missing.is_empty() {
+                    tracing::warn!(?missing, "access token is missing required scopes");
+                    state.set_logged_out();
+                    state.set_missing_scopes(missing);
                 }
             }
         });
@@ -92,7 +219,7 @@ impl Plugin for TwitchPlugin {
     fn on_inspector_message(
         &mut self,
         session: &PluginSessionHandle,
-        _inspector: Inspector,
+        inspector: Inspector,
         message: serde_json::Value,
     ) {
         let message: InspectorMessageIn = match serde_json::from_value(message) {
@@ -105,20 +232,116 @@ impl Plugin for TwitchPlugin {
                 self.state.update_inspector();
             }
             InspectorMessageIn::OpenAuthUrl => {
-                let redirect_url =
-                    Url::parse("https://tilepad.pages.dev/deep-link/com.jacobtread.tilepad.twitch")
-                        .expect("redirect url is hardcoded and must be valid");
+                let (url, _csrf) = auth_url(&required_scopes());
+                self.bot_auth_csrf = None;
 
-                let (url, _csrf) =
-                    ImplicitUserTokenBuilder::new(TWITCH_CLIENT_ID.into(), redirect_url)
-                        .set_scopes(TWITCH_REQUIRED_SCOPES.to_vec())
-                        .generate_url();
+                _ = session.open_url(url.to_string());
+            }
+            InspectorMessageIn::OpenBotAuthUrl => {
+                let (url, csrf) = auth_url(TWITCH_BOT_REQUIRED_SCOPES);
+                self.bot_auth_csrf = Some(csrf);
 
                 _ = session.open_url(url.to_string());
             }
+            InspectorMessageIn::BotLogout => {
+                self.state.set_bot_logged_out();
+                self.state
+                    .update_properties(session, |properties| properties.bot_access = None);
+            }
             InspectorMessageIn::Logout => {
+                self.state.set_missing_scopes(Vec::new());
                 self.state.set_logged_out();
-                _ = session.set_properties(Properties { access: None });
+                self.state
+                    .update_properties(session, |properties| properties.access = None);
+            }
+            InspectorMessageIn::SetIdle { idle } => {
+                self.state.set_idle(idle);
+            }
+            InspectorMessageIn::GetSocials => {
+                _ = inspector.send(InspectorMessageOut::Socials {
+                    socials: self.state.get_properties().socials,
+                });
+            }
+            InspectorMessageIn::SetSocials { socials } => {
+                self.state
+                    .update_properties(session, |properties| properties.socials = socials);
+            }
+            InspectorMessageIn::PreviewMessage { template } => {
+                _ = inspector.send(InspectorMessageOut::MessagePreview {
+                    text: self.state.expand_template(&template),
+                });
+            }
+            InspectorMessageIn::SetTitleOverflow { overflow } => {
+                self.state
+                    .update_properties(session, |properties| properties.title_overflow = overflow);
+            }
+            InspectorMessageIn::SetAutoRaidDefense { enabled, defense } => {
+                self.state.update_properties(session, |properties| {
+                    properties.auto_raid_defense = enabled;
+                    properties.raid_defense = defense;
+                });
+            }
+            InspectorMessageIn::SetPollInterval { secs } => {
+                self.state
+                    .update_properties(session, |properties| properties.poll_interval_secs = secs);
+            }
+            InspectorMessageIn::DispatchAction {
+                action_id,
+                properties,
+            } => {
+                let reporter = ActionReporter::Inspector {
+                    state: self.state.clone(),
+                    action_id: action_id.clone(),
+                };
+
+                if self.state.get_user_token().is_none() {
+                    tracing::debug!(?action_id, "ignoring dispatch while not authenticated");
+                    reporter.report(DeviceIndicator::Error);
+                    return;
+                }
+
+                let conditions = ActionConditions::from_properties(&properties);
+                let action = match Action::from_action(&action_id, properties) {
+                    Some(Ok(value)) => value,
+                    Some(Err(cause)) => {
+                        tracing::error!(?cause, ?action_id, "failed to deserialize action");
+                        reporter.report(DeviceIndicator::Error);
+                        return;
+                    }
+                    None => {
+                        tracing::debug!(?action_id, "unknown action dispatched");
+                        reporter.report(DeviceIndicator::Error);
+                        return;
+                    }
+                };
+
+                self.handle_action(
+                    session,
+                    format!("inspector:{action_id}"),
+                    action,
+                    conditions,
+                    reporter,
+                );
+            }
+            InspectorMessageIn::SelfTest => {
+                let state = self.state.clone();
+                spawn_local(async move { state.run_self_test().await });
+            }
+            InspectorMessageIn::RefreshAll => {
+                let state = self.state.clone();
+                spawn_local(async move { state.refresh_all().await });
+            }
+            InspectorMessageIn::GetRewards { refresh } => {
+                let state = self.state.clone();
+
+                spawn_local(async move {
+                    match state.get_rewards(refresh).await {
+                        Ok(rewards) => {
+                            state.send_inspector(InspectorMessageOut::Rewards { rewards })
+                        }
+                        Err(error) => tracing::error!(?error, "failed to get rewards"),
+                    }
+                });
             }
         }
     }
@@ -126,198 +349,186 @@ impl Plugin for TwitchPlugin {
     fn on_display_message(
         &mut self,
         _session: &PluginSessionHandle,
-        display: tilepad_plugin_sdk::Display,
+        display: Display,
         message: serde_json::Value,
     ) {
+        self.remember_tile_display(&display);
+
         let message: DisplayMessageIn = match serde_json::from_value(message) {
             Ok(value) => value,
             Err(_) => return,
         };
 
         match message {
-            DisplayMessageIn::GetViewCount => {
-                self.state.push_active_display(&display);
+            DisplayMessageIn::GetViewCount { refresh_secs } => {
+                self.state
+                    .push_active_display(&display, Metric::ViewCount, refresh_secs);
 
                 _ = display.send(DisplayMessageOut::ViewCount {
                     count: self.state.current_view_count(),
                 });
             }
-        }
-    }
+            DisplayMessageIn::GetViewCountTrend { refresh_secs } => {
+                self.state
+                    .push_active_display(&display, Metric::ViewCount, refresh_secs);
 
-    fn on_tile_clicked(
-        &mut self,
-        session: &PluginSessionHandle,
-        ctx: TileInteractionContext,
-        properties: serde_json::Value,
-    ) {
-        let action_id = ctx.action_id.as_str();
-        let action = match Action::from_action(action_id, properties) {
-            Some(Ok(value)) => value,
-            Some(Err(cause)) => {
-                tracing::error!(?cause, ?action_id, "failed to deserialize action");
-                return;
+                let delta = self.state.current_view_count_delta().unwrap_or_default();
+                _ = display.send(DisplayMessageOut::ViewCountTrend {
+                    count: self.state.current_view_count(),
+                    delta,
+                    trend: ViewCountTrend::from_delta(delta),
+                });
             }
-            None => {
-                tracing::debug!(?action_id, "unknown tile action requested");
-                return;
+            DisplayMessageIn::GetGoalProgress { refresh_secs } => {
+                self.state
+                    .push_active_display(&display, Metric::GoalProgress, refresh_secs);
+
+                let progress = self.state.current_goal_progress();
+                _ = display.send(DisplayMessageOut::GoalProgress {
+                    percent: progress.as_ref().map(|progress| progress.percent),
+                    label: progress.map(|progress| progress.label),
+                });
             }
-        };
-
-        let state = self.state.clone();
+            DisplayMessageIn::GetProfileImage => {
+                let state = self.state.clone();
 
-        let success_indicator = {
-            let session = session.clone();
+                spawn_local(async move {
+                    let url = match state.get_profile_image_url().await {
+                        Ok(value) => value,
+                        Err(error) => {
+                            tracing::error!(?error, "failed to get profile image");
+                            None
+                        }
+                    };
 
-            move || {
-                _ = session.display_indicator(
-                    ctx.device_id,
-                    ctx.tile_id,
-                    tilepad_plugin_sdk::DeviceIndicator::Success,
-                    1000,
-                );
+                    _ = display.send(DisplayMessageOut::ProfileImage { url });
+                });
             }
-        };
+            DisplayMessageIn::GetPendingRedemptions { refresh_secs } => {
+                self.state
+                    .push_active_display(&display, Metric::PendingRedemptions, refresh_secs);
 
-        let loading_indicator = {
-            let session = session.clone();
-
-            move || {
-                _ = session.display_indicator(
-                    ctx.device_id,
-                    ctx.tile_id,
-                    tilepad_plugin_sdk::DeviceIndicator::Loading,
-                    1000,
-                );
+                _ = display.send(DisplayMessageOut::PendingRedemptions {
+                    count: self.state.current_pending_redemptions(),
+                });
             }
-        };
-
-        let error_indicator = {
-            let session = session.clone();
+            DisplayMessageIn::GetClipsToday { refresh_secs } => {
+                self.state
+                    .push_active_display(&display, Metric::ClipsToday, refresh_secs);
 
-            move || {
-                _ = session.display_indicator(
-                    ctx.device_id,
-                    ctx.tile_id,
-                    tilepad_plugin_sdk::DeviceIndicator::Error,
-                    2500,
-                );
+                _ = display.send(DisplayMessageOut::ClipsToday {
+                    count: self.state.current_clips_today(),
+                });
             }
-        };
-
-        loading_indicator();
+            DisplayMessageIn::GetApiLatency { refresh_secs } => {
+                // Latency is measured by the view count poll
+                self.state
+                    .push_active_display(&display, Metric::ViewCount, refresh_secs);
 
-        match action {
-            Action::SendMessage(properties) => {
-                spawn_local(async move {
-                    let message = match properties.message {
-                        Some(value) => value,
-                        None => return,
-                    };
-
-                    if let Err(error) = state.send_chat_message(&message).await {
-                        tracing::error!(?error, "failed to send chat message");
-                        error_indicator();
-                    } else {
-                        success_indicator();
-                    }
+                _ = display.send(DisplayMessageOut::ApiLatency {
+                    millis: self.state.current_api_latency(),
                 });
             }
-            Action::ClearChat => {
-                spawn_local(async move {
-                    if let Err(error) = state.clear_chat().await {
-                        tracing::error!(?error, "failed to clear chat");
-                        error_indicator();
-                    } else {
-                        success_indicator();
-                    }
-                });
+            DisplayMessageIn::GetActiveInteractions { refresh_secs } => {
+                self.state
+                    .push_active_display(&display, Metric::ActiveInteractions, refresh_secs);
+
+                let (polls, predictions) = self.state.current_active_interactions();
+                _ = display.send(DisplayMessageOut::ActiveInteractions { polls, predictions });
             }
-            Action::EmoteOnly => {
-                spawn_local(async move {
-                    if let Err(error) = state.toggle_emote_only().await {
-                        tracing::error!(?error, "failed to toggle emote only chat");
-                        error_indicator();
-                    } else {
-                        success_indicator();
-                    }
+            DisplayMessageIn::GetCategoryViewers { refresh_secs } => {
+                self.state
+                    .push_active_display(&display, Metric::CategoryViewers, refresh_secs);
+
+                let viewers = self.state.current_category_viewers();
+                _ = display.send(DisplayMessageOut::CategoryViewers {
+                    total: viewers.map(|(total, _)| total),
+                    your_share_percent: viewers.map(|(_, share)| share),
                 });
             }
-            Action::FollowerOnly => {
-                spawn_local(async move {
-                    if let Err(error) = state.toggle_follower_only().await {
-                        tracing::error!(?error, "failed to toggle follower only chat");
-                        error_indicator();
-                    } else {
-                        success_indicator();
-                    }
+            DisplayMessageIn::GetFollowerCount { refresh_secs } => {
+                self.state
+                    .push_active_display(&display, Metric::FollowerCount, refresh_secs);
+
+                _ = display.send(DisplayMessageOut::FollowerCount {
+                    count: self.state.current_follower_count(),
                 });
             }
-            Action::SubOnly => {
-                spawn_local(async move {
-                    if let Err(error) = state.toggle_sub_only().await {
-                        tracing::error!(?error, "failed to toggle sub only chat");
-                        error_indicator();
-                    } else {
-                        success_indicator();
-                    }
+            DisplayMessageIn::GetUptime { refresh_secs } => {
+                self.state
+                    .push_active_display(&display, Metric::Uptime, refresh_secs);
+
+                _ = display.send(DisplayMessageOut::Uptime {
+                    seconds: self.state.current_uptime(),
                 });
             }
-            Action::SlowMode => {
-                spawn_local(async move {
-                    if let Err(error) = state.toggle_slow_mode().await {
-                        tracing::error!(?error, "failed to toggle slow mode");
-                        error_indicator();
-                    } else {
-                        success_indicator();
-                    }
+            DisplayMessageIn::GetCategory { refresh_secs } => {
+                self.state
+                    .push_active_display(&display, Metric::ChannelInfo, refresh_secs);
+
+                let channel = self.state.current_channel_info();
+                _ = display.send(DisplayMessageOut::Category {
+                    name: channel
+                        .map(|channel| channel.game_name.to_string())
+                        .filter(|name| !name.is_empty()),
                 });
             }
-            Action::AdBreak(properties) => {
-                spawn_local(async move {
-                    if let Err(error) = state
-                        .start_comercial(
-                            properties
-                                .length
-                                .unwrap_or(twitch_api::types::CommercialLength::Length30),
-                        )
-                        .await
-                    {
-                        tracing::error!(?error, "failed to create marker");
-                        error_indicator();
-                    } else {
-                        success_indicator();
-                    }
+            DisplayMessageIn::GetStreamTitle { refresh_secs } => {
+                self.state
+                    .push_active_display(&display, Metric::ChannelInfo, refresh_secs);
+
+                let channel = self.state.current_channel_info();
+                _ = display.send(DisplayMessageOut::StreamTitle {
+                    title: channel.map(|channel| channel.title),
                 });
             }
-            Action::Marker(properties) => {
-                spawn_local(async move {
-                    if let Err(error) = state
-                        .create_marker(properties.description.unwrap_or_default())
-                        .await
-                    {
-                        tracing::error!(?error, "failed to create marker");
-                        error_indicator();
-                    } else {
-                        success_indicator();
-                    }
+            DisplayMessageIn::GetActiveDisplays => {
+                _ = display.send(DisplayMessageOut::ActiveDisplays {
+                    count: self.state.get_total_active_displays(),
                 });
             }
-            Action::CreateClip => {
-                spawn_local(async move {
-                    if let Err(error) = state.create_clip().await {
-                        tracing::error!(?error, "failed to create clip");
-                        error_indicator();
-                    } else {
-                        success_indicator();
-                    }
-                });
+        }
+    }
+
+    fn on_tile_clicked(
+        &mut self,
+        session: &PluginSessionHandle,
+        ctx: TileInteractionContext,
+        properties: serde_json::Value,
+    ) {
+        let action_id = ctx.action_id.as_str();
+        let conditions = ActionConditions::from_properties(&properties);
+        let action = match Action::from_action(action_id, properties) {
+            Some(Ok(value)) => value,
+            Some(Err(cause)) => {
+                tracing::error!(?cause, ?action_id, "failed to deserialize action");
+                return;
             }
-            Action::OpenClip => {}
-            Action::ViewerCount => {
-                // No associated action (Maybe refresh manually when tapped?)
+            None => {
+                tracing::debug!(?action_id, "unknown tile action requested");
+                return;
             }
-        }
+        };
+
+        let display = self
+            .tile_displays
+            .iter()
+            .find(|display| display.ctx.tile_id == ctx.tile_id)
+            .cloned();
+
+        let reporter = ActionReporter::Tile {
+            session: session.clone(),
+            ctx: ctx.clone(),
+            display,
+        };
+
+        self.handle_action(
+            session,
+            ctx.tile_id.to_string(),
+            action,
+            conditions,
+            reporter,
+        );
     }
 
     fn on_deep_link(
@@ -337,15 +548,35 @@ impl Plugin for TwitchPlugin {
         };
 
         let access_token = fragment.access_token;
-        let scopes: Vec<Scope> = fragment
-            .scope
-            .split(':')
-            .map(|scope| Scope::parse(scope.to_string()))
-            .collect();
+        let scopes = parse_scopes(&fragment.scope);
+
+        // Authorization was for the bot account
+        let is_bot = self
+            .bot_auth_csrf
+            .take()
+            .is_some_and(|csrf| fragment.state.as_deref() == Some(csrf.secret()));
 
         // Try authenticates
         let session = session.clone();
         let state = self.state.clone();
+
+        if is_bot {
+            spawn_local(async move {
+                if let Err(error) = state.attempt_bot_auth(access_token.clone()).await {
+                    tracing::error!(?error, "failed to authenticate bot");
+                    return;
+                }
+
+                state.update_properties(&session, |properties| {
+                    properties.bot_access = Some(StoredAccess {
+                        access_token,
+                        scopes,
+                    })
+                });
+            });
+            return;
+        }
+
         spawn_local(async move {
             if let Err(error) = state.attempt_auth(access_token.clone()).await {
                 tracing::error!(?error, "failed to authenticate");
@@ -353,18 +584,159 @@ impl Plugin for TwitchPlugin {
             }
 
             // Store authentication credentials
-            _ = session.set_properties(Properties {
-                access: Some(StoredAccess {
+            state.update_properties(&session, |properties| {
+                properties.access = Some(StoredAccess {
                     access_token,
                     scopes,
-                }),
+                })
             });
         });
     }
 }
 
+/// Destination for reporting the progress of an action
+#[derive(Clone)]
+enum ActionReporter {
+    /// Action was triggered by a tile, progress is shown with indicators
+    Tile {
+        session: PluginSessionHandle,
+        ctx: TileInteractionContext,
+        /// Display of the tile, when the tile has one
+        display: Option<Display>,
+    },
+    /// Action was dispatched from the inspector, the result is sent back to it
+    Inspector { state: Rc<State>, action_id: String },
+}
+
+impl ActionReporter {
+    fn action_id(&self) -> &str {
+        match self {
+            ActionReporter::Tile { ctx, .. } => ctx.action_id.as_str(),
+            ActionReporter::Inspector { action_id, .. } => action_id,
+        }
+    }
+
+    /// Sends the result of the action to the display of the tile, the
+    /// inspector is informed of the result through [ActionReporter::report]
+    fn report_result(&self, success: bool, message: String) {
+        if let ActionReporter::Tile {
+            display: Some(display),
+            ..
+        } = self
+        {
+            _ = display.send(DisplayMessageOut::ActionResult { success, message });
+        }
+    }
+
+    fn report(&self, indicator: DeviceIndicator) {
+        match self {
+            ActionReporter::Tile { session, ctx, .. } => {
+                let duration = match indicator {
+                    DeviceIndicator::Error => 2500,
+                    _ => 1000,
+                };
+
+                _ = session.display_indicator(ctx.device_id, ctx.tile_id, indicator, duration);
+            }
+            ActionReporter::Inspector { state, action_id } => {
+                let success = match indicator {
+                    DeviceIndicator::Success => true,
+                    DeviceIndicator::Error => false,
+                    _ => return,
+                };
+
+                state.send_inspector(InspectorMessageOut::DispatchResult {
+                    action_id: action_id.clone(),
+                    success,
+                });
+            }
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct DeepLinkFragment {
     access_token: AccessToken,
     scope: String,
+    /// CSRF token provided when generating the auth URL
+    #[serde(default)]
+    state: Option<String>,
+}
+
+/// Scopes requested from the broadcaster, combines the background scopes
+/// with the scopes declared by every action
+pub fn required_scopes() -> Vec<Scope> {
+    let mut scopes: Vec<Scope> = Vec::new();
+    let action_scopes = ACTION_SCOPES.iter().flat_map(|(_, scopes)| scopes.iter());
+
+    for scope in TWITCH_BACKGROUND_SCOPES.iter().chain(action_scopes) {
+        if !scopes.contains(scope) {
+            scopes.push(scope.clone());
+        }
+    }
+
+    scopes
+}
+
+/// Scopes from `required` that are not present in `granted`
+fn missing_scopes(required: &[Scope], granted: &[Scope]) -> Vec<Scope> {
+    required
+        .iter()
+        .filter(|scope| !granted.contains(scope))
+        .cloned()
+        .collect()
+}
+
+/// Parses the space separated scopes twitch provides in the auth redirect
+fn parse_scopes(value: &str) -> Vec<Scope> {
+    value
+        .split_whitespace()
+        .map(|scope| Scope::parse(scope.to_string()))
+        .collect()
+}
+
+/// Creates an implicit grant URL for authorizing with the provided scopes
+fn auth_url(scopes: &[Scope]) -> (Url, CsrfToken) {
+    let redirect_url =
+        Url::parse("https://tilepad.pages.dev/deep-link/com.jacobtread.tilepad.twitch")
+            .expect("redirect url is hardcoded and must be valid");
+
+    ImplicitUserTokenBuilder::new(TWITCH_CLIENT_ID.into(), redirect_url)
+        .set_scopes(scopes.to_vec())
+        .generate_url()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scopes granted to tokens created by earlier versions of the plugin
+    const LEGACY_SCOPES: &str = "user:write:chat moderator:manage:chat_messages";
+
+    #[test]
+    fn parse_scopes_space_separated() {
+        assert_eq!(
+            parse_scopes(LEGACY_SCOPES),
+            vec![Scope::UserWriteChat, Scope::ModeratorManageChatMessages]
+        );
+    }
+
+    /// Tokens from earlier versions are missing the newly required scopes
+    /// but not the scopes they were already granted
+    #[test]
+    fn missing_scopes_after_upgrade() {
+        let required = required_scopes();
+        let missing = missing_scopes(&required, &parse_scopes(LEGACY_SCOPES));
+
+        assert!(missing.contains(&Scope::ChannelManageBroadcast));
+        assert!(!missing.contains(&Scope::UserWriteChat));
+        assert!(!missing.contains(&Scope::ModeratorManageChatMessages));
+        assert_eq!(missing.len(), required.len() - 2);
+    }
+
+    #[test]
+    fn missing_scopes_all_granted() {
+        let required = required_scopes();
+        assert!(missing_scopes(&required, &required).is_empty());
+    }
 }
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use twitch_api::{helix::Scope, twitch_oauth2::AccessToken};
+
+use crate::action::ChatMode;
+
+/// Properties for the plugin itself
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct Properties {
+    /// Access for the broadcaster, used for managing the channel
+    pub access: Option<StoredAccess>,
+
+    /// Access for a separate bot account used to send chat messages,
+    /// chat is sent as the broadcaster when not set
+    #[serde(default)]
+    pub bot_access: Option<StoredAccess>,
+
+    /// Social links posted by the socials action
+    #[serde(default)]
+    pub socials: Vec<SocialLink>,
+
+    /// Behavior when a stream title exceeds the twitch title limit
+    #[serde(default)]
+    pub title_overflow: TitleOverflow,
+
+    /// Whether protections are automatically applied when a raid is received
+    #[serde(default)]
+    pub auto_raid_defense: bool,
+
+    /// Protections applied when a raid is received
+    #[serde(default)]
+    pub raid_defense: RaidDefense,
+
+    /// Maximum number of actions allowed to execute at once
+    #[serde(default)]
+    pub max_concurrent_actions: Option<usize>,
+
+    /// Seconds between view count updates when no display requested
+    /// a specific interval
+    #[serde(default)]
+    pub poll_interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StoredAccess {
+    pub access_token: AccessToken,
+    pub scopes: Vec<Scope>,
+}
+
+/// Named link to one of the broadcasters socials
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SocialLink {
+    /// Name of the social platform (e.g "Discord")
+    pub name: String,
+    pub url: String,
+}
+
+/// Behavior for handling stream titles that are too long
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TitleOverflow {
+    /// Reject the title without updating the stream
+    #[default]
+    Reject,
+    /// Truncate the title to the maximum length
+    Truncate,
+}
+
+/// Protections applied for a short window after receiving a raid
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct RaidDefense {
+    /// Message posted when the raid arrives, supports {raider} and {viewers}
+    pub welcome: Option<String>,
+    /// Chat mode enabled while the defense is active
+    pub mode: Option<ChatMode>,
+    /// Seconds the defense is active for
+    pub duration: Option<u64>,
+}
@@ -0,0 +1,117 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::header::HeaderValue;
+use twitch_api::{
+    client::{BoxedFuture, Client, ClientDefault, Request, Response},
+    helix::{
+        ClientRequestError, HelixRequestDeleteError, HelixRequestGetError, HelixRequestPatchError,
+        HelixRequestPostError, HelixRequestPutError,
+    },
+};
+
+/// Header twitch uses to report when the rate limit is refilled (unix seconds)
+const RATELIMIT_RESET_HEADER: &str = "Ratelimit-Reset";
+
+/// Maximum number of times a rate limited request is retried
+pub const MAX_RATE_LIMIT_RETRIES: u32 = 1;
+
+/// Longest time to wait for the rate limit to reset before retrying
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(10);
+
+/// Time to wait before retrying when twitch has not reported a reset time
+const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(1);
+
+/// HTTP client for helix requests, keeps track of when the twitch rate
+/// limit resets so rate limited requests know how long to wait
+#[derive(Clone, Default)]
+pub struct RateLimitedClient {
+    client: reqwest::Client,
+    /// Time the rate limit resets (unix seconds), zero when unknown
+    reset_at: Arc<AtomicU64>,
+}
+
+impl RateLimitedClient {
+    /// Time remaining until the rate limit resets, capped to [MAX_RATE_LIMIT_WAIT]
+    pub fn reset_delay(&self) -> Duration {
+        let reset_at = self.reset_at.load(Ordering::Relaxed);
+        if reset_at == 0 {
+            return DEFAULT_RATE_LIMIT_WAIT;
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Duration::from_secs(reset_at)
+            .saturating_sub(now)
+            .min(MAX_RATE_LIMIT_WAIT)
+    }
+}
+
+impl Client for RateLimitedClient {
+    type Error = reqwest::Error;
+
+    fn req(&self, request: Request) -> BoxedFuture<'_, Result<Response, Self::Error>> {
+        Box::pin(async move {
+            let response = self.client.req(request).await?;
+
+            let reset_at = response
+                .headers()
+                .get(RATELIMIT_RESET_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok());
+
+            if let Some(reset_at) = reset_at {
+                self.reset_at.store(reset_at, Ordering::Relaxed);
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+impl ClientDefault<'static> for RateLimitedClient {
+    type Error = <reqwest::Client as ClientDefault<'static>>::Error;
+
+    fn default_client_with_name(product: Option<HeaderValue>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            client: reqwest::Client::default_client_with_name(product)?,
+            reset_at: Arc::default(),
+        })
+    }
+}
+
+/// Whether twitch rejected the request for exceeding the rate limit
+pub fn is_rate_limited<E>(error: &ClientRequestError<E>) -> bool
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let status = match error {
+        ClientRequestError::HelixRequestGetError(HelixRequestGetError::Error {
+            status, ..
+        })
+        | ClientRequestError::HelixRequestPostError(HelixRequestPostError::Error {
+            status, ..
+        })
+        | ClientRequestError::HelixRequestPatchError(HelixRequestPatchError::Error {
+            status,
+            ..
+        })
+        | ClientRequestError::HelixRequestDeleteError(HelixRequestDeleteError::Error {
+            status,
+            ..
+        })
+        | ClientRequestError::HelixRequestPutError(HelixRequestPutError::Error {
+            status, ..
+        }) => status,
+        _ => return false,
+    };
+
+    status.as_u16() == 429
+}
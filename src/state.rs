@@ -1,5 +1,10 @@
 use std::{
+    borrow::Cow,
     cell::{Cell, RefCell},
+    collections::HashMap,
+    hash::{BuildHasher, Hasher, RandomState},
+    ops::RangeInclusive,
+    path::Path,
     rc::Rc,
     time::{Duration, Instant},
 };
@@ -7,29 +12,248 @@ use std::{
 use anyhow::Context;
 use parking_lot::Mutex;
 use serde::Serialize;
-use tilepad_plugin_sdk::{Display, Inspector, tracing};
-use tokio::time::sleep;
+use tilepad_plugin_sdk::{Display, Inspector, PluginSessionHandle, tracing};
+use tokio::{
+    task::{JoinHandle, spawn_local},
+    time::{sleep, timeout},
+};
 use twitch_api::{
     HelixClient,
+    eventsub::{
+        Transport,
+        channel::{ChannelFollowV2, ChannelRaidV1, ChannelSubscribeV1},
+        stream::StreamOnlineV1,
+    },
     helix::{
-        EmptyBody, Request, RequestPost, Scope,
-        channels::{StartCommercial, StartCommercialBody, StartCommercialRequest},
+        ClientRequestError, EmptyBody, HelixRequestDeleteError, HelixRequestGetError,
+        HelixRequestPostError, Request, RequestPost, Scope,
+        channels::{
+            AddChannelVipRequest, ChannelInformation, ContentClassificationLabel,
+            GetChannelFollowersRequest, GetChannelInformationRequest, GetChannelVipsRequest,
+            ModifyChannelInformationBody, ModifyChannelInformationRequest, RemoveChannelVipRequest,
+            SnoozeNextAd, SnoozeNextAdRequest, StartCommercial, StartCommercialBody,
+            StartCommercialRequest,
+        },
         chat::{
-            ChatSettings, GetChatSettingsRequest, SendChatMessageBody, SendChatMessageRequest,
-            SendChatMessageResponse, UpdateChatSettingsBody, UpdateChatSettingsRequest,
+            AnnouncementColor, ChatSettings, GetChatSettingsRequest, SendAShoutoutRequest,
+            SendChatAnnouncementBody, SendChatAnnouncementRequest, SendChatMessageBody,
+            SendChatMessageRequest, SendChatMessageResponse, UpdateChatSettingsBody,
+            UpdateChatSettingsRequest, UpdateUserChatColorRequest,
+        },
+        clips::{Clip, CreateClipRequest, CreatedClip, GetClipsRequest},
+        goals::{CreatorGoalType, GetCreatorGoalsRequest},
+        moderation::{
+            AddChannelModeratorRequest, BanUserBody, BanUserRequest, DeleteChatMessagesRequest,
+            DeleteChatMessagesResponse, RemoveChannelModeratorRequest, WarnChatUserBody,
+            WarnChatUserRequest,
         },
-        clips::{CreateClipRequest, CreatedClip},
-        moderation::{DeleteChatMessagesRequest, DeleteChatMessagesResponse},
+        points::{
+            CustomRewardRedemptionStatus, GetCustomRewardRedemptionRequest, GetCustomRewardRequest,
+            UpdateCustomRewardBody, UpdateCustomRewardRequest, UpdateRedemptionStatusBody,
+            UpdateRedemptionStatusRequest,
+        },
+        polls::{
+            CreatePollBody, CreatePollRequest, EndPollBody, EndPollRequest, GetPollsRequest,
+            NewPollChoice, PollStatus,
+        },
+        predictions::{
+            CreatePredictionBody, CreatePredictionRequest, EndPredictionBody, EndPredictionRequest,
+            GetPredictionsRequest, NewPredictionOutcome, Prediction, PredictionStatus,
+        },
+        raids::{CancelARaidRequest, StartARaidRequest},
+        schedule::GetChannelStreamScheduleRequest,
+        search::SearchCategoriesRequest,
         streams::{
             CreateStreamMarkerBody, CreateStreamMarkerRequest, CreatedStreamMarker,
             GetStreamsRequest,
         },
+        users::{BlockUserRequest, GetUsersRequest, UnblockUserRequest, User},
+    },
+    twitch_oauth2::{AccessToken, TwitchToken, UserToken, Validator, validator},
+    types::{
+        ClipId, CommercialLength, ContentClassificationId, NamedUserColor, PollId, PredictionId,
+        RewardId, Timestamp, UserId, UserName,
     },
-    twitch_oauth2::{AccessToken, UserToken, Validator, validator},
-    types::CommercialLength,
 };
 
-use crate::messages::InspectorMessageOut;
+use crate::{
+    action::{ACTION_SCOPES, ChatMode},
+    displays::{DisplayRegistry, Metric},
+    error::AuthError,
+    format::{
+        chunk_chars, format_compact_duration, format_count, format_date, format_standings,
+        format_timestamp, join_chunked, timestamp_seconds, uptime_seconds,
+    },
+    limiter::{ActionLimiter, ActionSlot, DEFAULT_MAX_CONCURRENT_ACTIONS},
+    marker_log::{
+        ChapterFormat, MARKER_LOG_FILE, MarkerLogEntry, append_marker_log, format_chapters,
+        write_chapters,
+    },
+    messages::{InspectorMessageOut, RewardSummary},
+    properties::{Properties, TitleOverflow},
+    ratelimit::{MAX_RATE_LIMIT_RETRIES, RateLimitedClient, is_rate_limited},
+    template::expand_template,
+};
+
+/// Maximum number of pages of category streams to search through when
+/// looking for the broadcasters rank (100 streams per page)
+const MAX_RANK_SEARCH_PAGES: usize = 5;
+
+/// Maximum number of pages of clips to count when counting the clips
+/// created today (100 clips per page)
+const MAX_CLIP_COUNT_PAGES: usize = 5;
+
+/// Maximum number of pages of category streams counted when summing the
+/// viewers in the broadcasters category (100 streams per page)
+const MAX_CATEGORY_VIEWER_PAGES: usize = 5;
+
+/// Maximum number of pages of redemptions to count for each reward when
+/// counting pending redemptions (50 redemptions per page)
+const MAX_REDEMPTION_PAGES: usize = 4;
+
+/// Maximum length of a stream marker description
+pub const MAX_MARKER_DESCRIPTION_LENGTH: usize = 140;
+
+/// Maximum length of a stream title
+pub const MAX_TITLE_LENGTH: usize = 140;
+
+/// Default template used when posting interaction standings
+pub const DEFAULT_STANDINGS_TEMPLATE: &str = "{title}: {standings}";
+
+/// Default announcement posted by the sub hype action
+pub const DEFAULT_SUB_HYPE_MESSAGE: &str = "Thanks for the sub! Hype in chat!";
+
+/// Default template used when posting the chat status
+pub const DEFAULT_CHAT_STATUS_TEMPLATE: &str = "Chat is in {restrictions}";
+
+/// Default warning announced before clearing chat, supports {seconds}
+pub const DEFAULT_CLEAR_CHAT_WARNING: &str = "Chat is being cleared in {seconds} seconds";
+
+/// Longest delay allowed between the clear chat warning and clearing chat (seconds)
+pub const MAX_CLEAR_CHAT_DELAY: u64 = 30;
+
+/// Default message posted with a random clip, supports {url}, {title} and {creator}
+pub const DEFAULT_RANDOM_CLIP_TEMPLATE: &str = "Check out this clip! {url}";
+
+/// Only clips created within this many days are picked from for a random clip
+const RANDOM_CLIP_WINDOW_DAYS: i64 = 30;
+
+/// Maximum number of clips picked from for a random clip
+const RANDOM_CLIP_POOL_SIZE: usize = 100;
+
+/// Default message posted when entering focus mode
+pub const DEFAULT_FOCUS_ENTER_MESSAGE: &str = "Entering focus mode — subs can chat";
+
+/// Default message posted when leaving focus mode
+pub const DEFAULT_FOCUS_EXIT_MESSAGE: &str = "Leaving focus mode — everyone can chat";
+
+/// Default message posted for the next scheduled stream, supports {title} and {time}
+pub const DEFAULT_NEXT_STREAM_TEMPLATE: &str = "Next stream: {title} in {time}";
+
+/// Maximum number of characters in a single chat message
+pub const MAX_CHAT_MESSAGE_LENGTH: usize = 500;
+
+/// Longest timeout twitch allows (seconds)
+pub const MAX_TIMEOUT_DURATION: u32 = 1_209_600;
+
+/// Number of choices twitch allows on a poll
+pub const POLL_CHOICES_RANGE: RangeInclusive<usize> = 2..=5;
+
+/// Duration twitch allows for a poll (seconds)
+pub const POLL_DURATION_RANGE: RangeInclusive<u32> = 15..=1800;
+
+/// Default duration of a poll (seconds)
+pub const DEFAULT_POLL_DURATION: u32 = 60;
+
+/// Number of outcomes twitch allows on a prediction
+pub const PREDICTION_OUTCOMES_RANGE: RangeInclusive<usize> = 2..=10;
+
+/// Prediction window twitch allows (seconds)
+pub const PREDICTION_WINDOW_RANGE: RangeInclusive<u32> = 30..=1800;
+
+/// Default prediction window (seconds)
+pub const DEFAULT_PREDICTION_WINDOW: u32 = 120;
+
+/// Drop reason code twitch uses when a message matches the previous message
+const DUPLICATE_MESSAGE_CODE: &str = "msg_duplicate";
+
+/// Invisible character appended to a duplicate message when no suffixes are
+/// configured, makes the message differ without changing how it appears
+const INVISIBLE_SUFFIX: &str = "\u{E0000}";
+
+/// Maximum number of pages of VIPs searched when checking VIP status
+const MAX_VIP_PAGES: usize = 5;
+
+/// Non moderator chat delays accepted by twitch (seconds)
+pub const ALLOWED_CHAT_DELAYS: [u64; 3] = [2, 4, 6];
+
+/// Latency samples older than this are no longer reported
+pub const API_LATENCY_MAX_AGE: Duration = Duration::from_secs(30);
+
+/// Age after which the view count is fetched again before checking the
+/// minimum viewers of an action
+const CONDITION_VIEW_COUNT_MAX_AGE: Duration = Duration::from_secs(30);
+
+/// Default message used when thanking the last raid
+pub const DEFAULT_RAID_THANKS_TEMPLATE: &str =
+    "Thanks for the raid @{raider} with {viewers} raiders!";
+
+/// Raids older than this are not considered recent enough to thank
+pub const RECENT_RAID_WINDOW: Duration = Duration::from_secs(60 * 30);
+
+/// Default template used when announcing goal progress
+pub const DEFAULT_GOAL_TEMPLATE: &str = "We're {percent}% to {target} {unit}!";
+
+/// Default format for `{date}` within templates
+pub const DEFAULT_DATE_FORMAT: &str = "YYYY-MM-DD";
+
+/// Interval between checks for a created clip finishing processing
+const CLIP_READY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Default number of seconds raid defense remains active
+const DEFAULT_RAID_DEFENSE_DURATION: u64 = 120;
+
+/// Shortest slow mode wait time twitch allows (seconds)
+const MIN_SLOW_MODE_WAIT: u64 = 3;
+
+/// Longest slow mode wait time twitch allows (seconds)
+const MAX_SLOW_MODE_WAIT: u64 = 120;
+
+/// Longest follower only duration twitch allows (minutes)
+const MAX_FOLLOWER_MODE_DURATION: u64 = 129_600;
+
+/// Scopes the broadcaster token must have to manage the channel
+const BROADCASTER_REQUIRED_SCOPES: &[Scope] = &[Scope::ChannelManageBroadcast];
+
+/// Scopes the bot token must have to send chat messages
+const BOT_REQUIRED_SCOPES: &[Scope] = &[Scope::UserWriteChat];
+
+/// Maximum time to wait for twitch to validate a token
+const AUTH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Maximum number of attempts made to authenticate when twitch cannot be reached
+const MAX_AUTH_ATTEMPTS: u32 = 4;
+
+/// Delay before the first auth retry, doubled after each further attempt
+const AUTH_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Interval between checks that the access tokens are still valid
+const TOKEN_VALIDATION_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Default seconds between view count updates
+pub const DEFAULT_POLL_INTERVAL_SECS: u64 = 5;
+
+/// Allowed range for the configured view count polling interval (seconds)
+pub const POLL_INTERVAL_RANGE: RangeInclusive<u64> = 2..=60;
+
+/// Interval the view count is polled at while the stream is offline
+const OFFLINE_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Interval the metrics loop checks for metrics that need refreshing
+const METRICS_TICK: Duration = Duration::from_secs(1);
+
+/// Shortest interval metrics are refreshed at while the plugin is idle
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(30);
 
 #[derive(Default)]
 #[allow(clippy::large_enum_variant)]
@@ -44,277 +268,3075 @@ pub enum AccessState {
 
 #[derive(Default)]
 pub struct State {
-    helix_client: HelixClient<'static, reqwest::Client>,
+    helix_client: HelixClient<'static, RateLimitedClient>,
     access_state: Mutex<AccessState>,
+    /// Token for the bot account sending chat messages when configured
+    bot_token: RefCell<Option<UserToken>>,
+    /// Required scopes missing from the stored access
+    missing_scopes: RefCell<Vec<Scope>>,
     inspector: RefCell<Option<Inspector>>,
 
-    view_displays: RefCell<Vec<ViewCountDisplay>>,
-    viewers: Cell<usize>,
-}
+    /// Current plugin properties
+    properties: RefCell<Properties>,
+
+    /// Limits the number of actions executing at once
+    action_limiter: ActionLimiter,
+
+    /// Whether polling is paused to save API calls
+    idle: Cell<bool>,
+    /// Whether lurk mode is active, polling is also slowed while lurking
+    lurking: Cell<bool>,
+
+    /// Displays requesting values polled from twitch
+    displays: DisplayRegistry,
+
+    viewers: Cell<usize>,
+    /// Change in viewers since the previous poll, [None] while offline or
+    /// before the first online sample (viewers is not a real sample then)
+    viewers_delta: Cell<Option<i64>>,
+    /// Whether the last view count poll found the stream offline
+    stream_offline: Cell<bool>,
+    /// Round trip time of the last view count poll and when it was measured
+    api_latency: Cell<Option<(Duration, Instant)>>,
+
+    goal_progress: RefCell<Option<GoalProgress>>,
+
+    /// Cached profile image URL of the authenticated user
+    profile_image_url: RefCell<Option<String>>,
+
+    /// Profile of the authenticated user, shown in the inspector
+    user: RefCell<Option<User>>,
+
+    clips_today: Cell<usize>,
+
+    pending_redemptions: Cell<usize>,
+
+    /// Number of active polls and predictions
+    active_interactions: Cell<(usize, usize)>,
+
+    /// Total viewers in the current category and the broadcasters share of them
+    category_viewers: Cell<Option<(usize, f64)>>,
+
+    /// Total followers of the channel
+    follower_count: Cell<Option<u64>>,
+
+    /// Time the current stream started (seconds since the unix epoch),
+    /// [None] while offline
+    stream_started_at: Cell<Option<i64>>,
+
+    /// Channel information (category, title), available while offline
+    channel_info: RefCell<Option<ChannelInformation>>,
+
+    /// Markers captured during this session
+    marker_log: RefCell<Vec<MarkerLogEntry>>,
+
+    /// Number of followers gained since the stream started
+    followers_gained: Cell<u64>,
+
+    /// Login of the most recent follower received through EventSub
+    latest_follower: RefCell<Option<String>>,
+
+    /// Most recent raid received through EventSub
+    last_raid: RefCell<Option<IncomingRaid>>,
+
+    /// Login of the most recent subscriber received through EventSub
+    latest_subscriber: RefCell<Option<String>>,
+
+    /// ID of the most recent poll created by the plugin
+    last_poll: RefCell<Option<PollId>>,
+
+    /// Most recent prediction created by the plugin
+    last_prediction: RefCell<Option<Prediction>>,
+
+    /// Whether a warned chat clear is waiting to happen
+    clear_chat_pending: Cell<bool>,
+
+    /// Scheduled removal tasks for temporary VIPs, keyed by user ID
+    temp_vips: RefCell<HashMap<UserId, JoinHandle<()>>>,
+
+    /// Whether sub only chat was enabled before entering focus mode,
+    /// [None] when focus mode is not active
+    focus_mode: Cell<Option<bool>>,
+
+    /// Incremented whenever chat settings are changed, timed restore tasks
+    /// compare against this to avoid overwriting newer changes
+    settings_generation: Cell<u64>,
+
+    /// Category rank and category name from the last rank change check
+    last_rank: RefCell<Option<(usize, String)>>,
+
+    /// Cached list of rewards manageable by the plugin
+    rewards: RefCell<Option<Vec<RewardSummary>>>,
+
+    /// Current position of cycling actions keyed by tile
+    cycle_indices: RefCell<HashMap<String, usize>>,
+
+    /// Label used for markers, selected by the marker category action
+    marker_category: RefCell<Option<String>>,
+
+    /// Chat color last applied by the toggle action for each account
+    chat_colors: RefCell<HashMap<UserId, NamedUserColor<'static>>>,
+}
+
+impl State {
+    pub fn set_inspector(&self, inspector: Option<Inspector>) {
+        *self.inspector.borrow_mut() = inspector;
+    }
+
+    pub fn set_properties(&self, properties: Properties) {
+        self.action_limiter.set_limit(
+            properties
+                .max_concurrent_actions
+                .unwrap_or(DEFAULT_MAX_CONCURRENT_ACTIONS),
+        );
+
+        *self.properties.borrow_mut() = properties;
+    }
+
+    /// Waits for a slot to execute an action, see [ActionLimiter]
+    pub async fn acquire_action_slot(&self) -> Option<ActionSlot<'_>> {
+        self.action_limiter.acquire().await
+    }
+
+    pub fn get_properties(&self) -> Properties {
+        self.properties.borrow().clone()
+    }
+
+    /// Applies the update to the current properties and persists them
+    pub fn update_properties(
+        &self,
+        session: &PluginSessionHandle,
+        update: impl FnOnce(&mut Properties),
+    ) {
+        let properties = &mut *self.properties.borrow_mut();
+        update(properties);
+        _ = session.set_properties(&*properties);
+    }
+
+    pub fn set_logged_out(&self) {
+        self.profile_image_url.replace(None);
+        self.user.replace(None);
+
+        let state = &mut *self.access_state.lock();
+        *state = AccessState::NotAuthenticate;
+        self.update_inspector();
+    }
+
+    pub fn update_inspector(&self) {
+        if let Some(inspector) = self.inspector.borrow().as_ref() {
+            // Lock must be released before checking the available actions
+            {
+                let state = &*self.access_state.lock();
+                match state {
+                    AccessState::NotAuthenticate => {
+                        _ = inspector.send(InspectorMessageOut::State {
+                            state: "NOT_AUTHENTICATED".to_string(),
+                        });
+                    }
+                    AccessState::Loading => {
+                        _ = inspector.send(InspectorMessageOut::State {
+                            state: "LOADING".to_string(),
+                        });
+                    }
+                    AccessState::Authenticated { user_token } => {
+                        _ = inspector.send(InspectorMessageOut::State {
+                            state: "AUTHENTICATED".to_string(),
+                        });
+
+                        // Tokens that never expire report the maximum duration
+                        let expires_in = user_token.expires_in();
+                        _ = inspector.send(InspectorMessageOut::TokenExpiry {
+                            expires_in_secs: (expires_in != Duration::MAX)
+                                .then(|| expires_in.as_secs()),
+                        });
+                    }
+                }
+            }
+
+            _ = inspector.send(InspectorMessageOut::BotState {
+                login: self
+                    .bot_token
+                    .borrow()
+                    .as_ref()
+                    .map(|token| token.login.to_string()),
+            });
+
+            if let Some(user) = self.user.borrow().as_ref() {
+                _ = inspector.send(user_info_message(user));
+            }
+
+            _ = inspector.send(InspectorMessageOut::MissingScopes {
+                scopes: self.missing_scopes.borrow().clone(),
+            });
+
+            _ = inspector.send(InspectorMessageOut::AvailableActions {
+                actions: self.available_actions(),
+            });
+
+            _ = inspector.send(InspectorMessageOut::Idle {
+                idle: self.idle.get(),
+            });
+        }
+    }
+
+    /// Sends a message to the inspector if one is open
+    pub fn send_inspector(&self, message: InspectorMessageOut) {
+        if let Some(inspector) = self.inspector.borrow().as_ref() {
+            _ = inspector.send(message);
+        }
+    }
+
+    pub fn set_idle(&self, idle: bool) {
+        self.idle.set(idle);
+        self.update_inspector();
+    }
+
+    /// Whether polling is slowed, either idle was set from the inspector
+    /// or lurk mode is active
+    pub fn is_idle(&self) -> bool {
+        self.idle.get() || self.lurking.get()
+    }
+
+    /// Provides the delay before the next poll, polling is slowed down while idle
+    pub fn poll_interval(&self, interval: Duration) -> Duration {
+        if self.is_idle() {
+            interval.max(IDLE_POLL_INTERVAL)
+        } else {
+            interval
+        }
+    }
+
+    /// Runs the request, retrying when twitch rejects it for exceeding the
+    /// rate limit. Retries wait for the rate limit to reset and are limited
+    /// to [MAX_RATE_LIMIT_RETRIES]
+    pub async fn req_with_retry<T, F, Fut>(
+        &self,
+        request: F,
+    ) -> Result<T, ClientRequestError<reqwest::Error>>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, ClientRequestError<reqwest::Error>>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match request().await {
+                Err(error) if is_rate_limited(&error) && attempt < MAX_RATE_LIMIT_RETRIES => {
+                    let delay = self.helix_client.get_client().reset_delay();
+                    tracing::warn!(?delay, "rate limited by twitch, retrying");
+                    sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub async fn create_user_token(
+        &self,
+        access_token: AccessToken,
+    ) -> Result<UserToken, AuthError> {
+        let user_token = timeout(
+            AUTH_TIMEOUT,
+            UserToken::from_existing(&self.helix_client, access_token, None, None),
+        )
+        .await
+        .map_err(|_| AuthError::Timeout)??;
+        Ok(user_token)
+    }
+
+    /// Validates the access token with twitch, retrying when twitch cannot
+    /// be reached (i.e the network is not ready yet). The token must have
+    /// every one of the `required` scopes
+    async fn validate_access(
+        &self,
+        access_token: AccessToken,
+        required: &[Scope],
+    ) -> Result<UserToken, AuthError> {
+        let mut attempt = 1;
+        let user_token = loop {
+            match self.create_user_token(access_token.clone()).await {
+                Ok(value) => break value,
+                Err(error) if error.is_transient() && attempt < MAX_AUTH_ATTEMPTS => {
+                    let delay = AUTH_RETRY_DELAY * 2u32.pow(attempt - 1);
+                    tracing::warn!(?error, ?delay, attempt, "auth attempt failed, retrying");
+                    sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(error) => {
+                    if matches!(error, AuthError::Timeout) {
+                        self.send_inspector(InspectorMessageOut::AuthTimeout);
+                    }
+
+                    return Err(error);
+                }
+            }
+        };
+
+        let missing: Vec<Scope> = required
+            .iter()
+            .filter(|scope| !user_token.scopes().contains(scope))
+            .cloned()
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(AuthError::MissingScopes(missing));
+        }
+
+        Ok(user_token)
+    }
+
+    pub async fn attempt_auth(&self, access_token: AccessToken) -> Result<(), AuthError> {
+        {
+            let lock = &mut *self.access_state.lock();
+            *lock = AccessState::Loading;
+        }
+
+        self.update_inspector();
+
+        let user_token = match self
+            .validate_access(access_token, BROADCASTER_REQUIRED_SCOPES)
+            .await
+        {
+            Ok(value) => value,
+            Err(error) => {
+                self.set_logged_out();
+                return Err(error);
+            }
+        };
+
+        {
+            let lock = &mut *self.access_state.lock();
+            *lock = AccessState::Authenticated { user_token };
+        }
+
+        self.missing_scopes.replace(Vec::new());
+
+        self.update_inspector();
+
+        if let Err(error) = self.fetch_user_info().await {
+            tracing::warn!(?error, "failed to fetch user info");
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the profile of the authenticated user and sends it to the inspector
+    async fn fetch_user_info(&self) -> anyhow::Result<()> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = GetUsersRequest::ids(vec![user_id]);
+        let response = self.helix_client.req_get(request, &token).await?.data;
+        let user = response.into_iter().next().context("user not found")?;
+
+        self.profile_image_url
+            .replace(user.profile_image_url.clone());
+        self.send_inspector(user_info_message(&user));
+        self.user.replace(Some(user));
+        Ok(())
+    }
+
+    /// Authenticates the bot account used for sending chat messages
+    pub async fn attempt_bot_auth(&self, access_token: AccessToken) -> Result<(), AuthError> {
+        let user_token = self
+            .validate_access(access_token, BOT_REQUIRED_SCOPES)
+            .await?;
+
+        self.bot_token.replace(Some(user_token));
+        self.update_inspector();
+
+        Ok(())
+    }
+
+    /// Stops sending chat messages as the bot account
+    pub fn set_bot_logged_out(&self) {
+        self.bot_token.replace(None);
+        self.update_inspector();
+    }
+
+    pub fn set_missing_scopes(&self, scopes: Vec<Scope>) {
+        self.missing_scopes.replace(scopes);
+        self.update_inspector();
+    }
+
+    pub fn get_bot_token(&self) -> Option<UserToken> {
+        self.bot_token.borrow().clone()
+    }
+
+    /// Checks that twitch still accepts the token
+    pub async fn check_token(&self, token: &UserToken) -> Result<(), AuthError> {
+        timeout(AUTH_TIMEOUT, token.validate_token(&self.helix_client))
+            .await
+            .map_err(|_| AuthError::Timeout)??;
+        Ok(())
+    }
+
+    /// IDs of the actions the authenticated user granted every required
+    /// scope for, empty when not authenticated
+    pub fn available_actions(&self) -> Vec<&'static str> {
+        let token = match self.get_user_token() {
+            Some(value) => value,
+            None => return Vec::new(),
+        };
+
+        let granted = token.scopes();
+        ACTION_SCOPES
+            .iter()
+            .filter(|(_, scopes)| scopes.iter().all(|scope| granted.contains(scope)))
+            .map(|(action_id, _)| *action_id)
+            .collect()
+    }
+
+    pub fn get_user_token(&self) -> Option<UserToken> {
+        let lock = &*self.access_state.lock();
+        match lock {
+            AccessState::Authenticated { user_token } => Some(user_token.clone()),
+            _ => None,
+        }
+    }
+
+    /// Runs a series of read only checks against twitch reporting the result
+    /// of each to the inspector followed by a summary
+    pub async fn run_self_test(&self) {
+        let mut results = Vec::new();
+
+        let token = self.get_user_token();
+        let authenticated: anyhow::Result<()> = match &token {
+            Some(_) => Ok(()),
+            None => Err(anyhow::anyhow!("not authenticated")),
+        };
+        results.push(self.report_self_test_step("authenticated", authenticated));
+
+        if let Some(token) = token {
+            let validated = token
+                .validate_token(&self.helix_client)
+                .await
+                .map(|_| ())
+                .map_err(anyhow::Error::from);
+            results.push(self.report_self_test_step("validate token", validated));
+
+            let user = self.get_user_id_by_login(token.login.as_str()).await;
+            results.push(self.report_self_test_step("fetch user", user.map(|_| ())));
+
+            let channel = self.get_channel_information().await;
+            results.push(self.report_self_test_step("fetch channel", channel.map(|_| ())));
+
+            let settings = self.get_chat_settings().await;
+            results.push(self.report_self_test_step("fetch chat settings", settings.map(|_| ())));
+        }
+
+        let passed = results.iter().filter(|success| **success).count();
+        self.send_inspector(InspectorMessageOut::SelfTestSummary {
+            passed,
+            failed: results.len() - passed,
+        });
+    }
+
+    /// Reports the result of a self test step to the inspector, returns
+    /// whether the step passed
+    fn report_self_test_step(&self, step: &str, result: anyhow::Result<()>) -> bool {
+        let error = result.err().map(|error| {
+            tracing::warn!(?error, step, "self test step failed");
+            format!("{error:#}")
+        });
+        let success = error.is_none();
+
+        self.send_inspector(InspectorMessageOut::SelfTestStep {
+            step: step.to_string(),
+            success,
+            error,
+        });
+
+        success
+    }
+
+    /// Resolves a twitch login name to the ID of the user
+    pub async fn get_user_id_by_login(&self, login: &str) -> anyhow::Result<UserId> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let request = GetUsersRequest::logins(vec![UserName::from(login)]);
+        let response = self.helix_client.req_get(request, &token).await?.data;
+        let user = response
+            .into_iter()
+            .next()
+            .with_context(|| format!("user \"{login}\" not found"))?;
+
+        Ok(user.id)
+    }
+
+    /// Clears every cached value so the next read fetches fresh data from twitch
+    pub fn invalidate_all(&self) {
+        self.profile_image_url.replace(None);
+        self.user.replace(None);
+        self.rewards.replace(None);
+        self.goal_progress.replace(None);
+        self.last_rank.replace(None);
+        self.viewers.set(0);
+        self.viewers_delta.set(None);
+        self.api_latency.set(None);
+        self.clips_today.set(0);
+        self.pending_redemptions.set(0);
+        self.active_interactions.set((0, 0));
+        self.category_viewers.set(None);
+        self.follower_count.set(None);
+        self.stream_started_at.set(None);
+        self.channel_info.replace(None);
+    }
+
+    /// Clears every cache and fetches the essential values again, used when
+    /// state changed on twitch outside of the plugin. Completion is reported
+    /// to the inspector
+    pub async fn refresh_all(&self) {
+        self.invalidate_all();
+
+        let (view_count, goal_progress, user_info, channel_info, rewards) = tokio::join!(
+            self.get_view_count(),
+            self.get_goal_progress(),
+            self.fetch_user_info(),
+            self.get_channel_information(),
+            self.get_rewards(true)
+        );
+
+        let mut success = true;
+
+        match view_count {
+            Ok(view_count) => {
+                self.viewers.set(view_count.unwrap_or_default());
+                self.stream_offline.set(view_count.is_none());
+                // Marks the count as a sample for the next poll to compare against
+                self.viewers_delta.set(view_count.map(|_| 0));
+            }
+            Err(error) => {
+                tracing::error!(?error, "refresh: failed to get view count");
+                success = false;
+            }
+        }
+
+        match goal_progress {
+            Ok(progress) => {
+                self.goal_progress.replace(progress);
+            }
+            Err(error) => {
+                tracing::error!(?error, "refresh: failed to get goal progress");
+                success = false;
+            }
+        }
+
+        if let Err(error) = user_info {
+            tracing::error!(?error, "refresh: failed to get user info");
+            success = false;
+        }
+
+        match channel_info {
+            Ok(channel) => {
+                self.channel_info.replace(Some(channel));
+            }
+            Err(error) => {
+                tracing::error!(?error, "refresh: failed to get channel information");
+                success = false;
+            }
+        }
+
+        match rewards {
+            Ok(rewards) => self.send_inspector(InspectorMessageOut::Rewards { rewards }),
+            Err(error) => {
+                tracing::error!(?error, "refresh: failed to get rewards");
+                success = false;
+            }
+        }
+
+        self.send_inspector(InspectorMessageOut::RefreshComplete { success });
+    }
+
+    /// Gets the profile image URL of the authenticated user, the URL is
+    /// cached after the first request
+    pub async fn get_profile_image_url(&self) -> anyhow::Result<Option<String>> {
+        if let Some(url) = self.profile_image_url.borrow().as_ref() {
+            return Ok(Some(url.clone()));
+        }
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = GetUsersRequest::ids(vec![user_id]);
+        let response = self.helix_client.req_get(request, &token).await?.data;
+        let url = response
+            .into_iter()
+            .next()
+            .and_then(|user| user.profile_image_url);
+
+        self.profile_image_url.replace(url.clone());
+        Ok(url)
+    }
+
+    /// Values available to message templates, derived from cached state
+    pub fn template_values(&self) -> Vec<(&'static str, String)> {
+        let mut values = vec![
+            ("date", format_date(&Timestamp::now(), DEFAULT_DATE_FORMAT)),
+            ("viewers", self.current_view_count().to_string()),
+            ("followers_gained", self.followers_gained.get().to_string()),
+        ];
+
+        if let Some(goal) = self.current_goal_progress() {
+            values.push(("goal", goal.label));
+        }
+
+        if let Some(token) = self.get_user_token() {
+            values.push(("login", token.login.to_string()));
+        }
+
+        if let Some(login) = self.latest_follower() {
+            values.push(("latest_follower", login));
+        }
+
+        if let Some(login) = self.latest_subscriber() {
+            values.push(("latest_sub", login));
+        }
+
+        values
+    }
+
+    /// Expands the template using the current cached values
+    pub fn expand_template(&self, template: &str) -> String {
+        expand_template(template, &self.template_values())
+    }
+
+    pub async fn send_chat_message(
+        &self,
+        message: &str,
+    ) -> anyhow::Result<SendChatMessageResponse> {
+        // Obtain twitch access token
+        let token = self.get_user_token().context("not authenticated")?;
+
+        // Get broadcaster user ID
+        let user_id = token.user_id.clone();
+
+        // Messages are sent by the bot account when one is configured
+        let sender = self.get_bot_token().unwrap_or(token);
+
+        // Create chat message request
+        let request = SendChatMessageRequest::new();
+        let body = SendChatMessageBody::new(user_id, sender.user_id.clone(), message);
+
+        // Send request and get response
+        let response: SendChatMessageResponse = self
+            .req_with_retry(|| {
+                self.helix_client
+                    .req_post(request.clone(), body.clone(), &sender)
+            })
+            .await?
+            .data;
+
+        Ok(response)
+    }
+
+    /// Sends a message to Twitch chat, when twitch drops the message as a duplicate
+    /// of the previous message it is sent once more with the next suffix from
+    /// `suffixes` appended (or an invisible character when there are none).
+    ///
+    /// Returns the message that was sent
+    pub async fn send_chat_message_avoiding_duplicates(
+        &self,
+        message: &str,
+        suffixes: &[String],
+    ) -> anyhow::Result<String> {
+        let response = self.send_chat_message(message).await?;
+        if response.is_sent {
+            return Ok(message.to_string());
+        }
+
+        let is_duplicate = response
+            .drop_reason
+            .as_ref()
+            .is_some_and(|reason| reason.code.as_str() == DUPLICATE_MESSAGE_CODE);
+
+        if !is_duplicate {
+            anyhow::bail!("message was dropped: {:?}", response.drop_reason);
+        }
+
+        let suffix = match suffixes.is_empty() {
+            true => INVISIBLE_SUFFIX,
+            false => {
+                let index = self.next_cycle_index("duplicate_suffix", suffixes.len());
+                suffixes[index].as_str()
+            }
+        };
+
+        // Only retry once to avoid looping on messages that are always dropped
+        let message = format!("{message} {suffix}");
+        let response = self.send_chat_message(&message).await?;
+        if !response.is_sent {
+            anyhow::bail!(
+                "message was dropped after retrying: {:?}",
+                response.drop_reason
+            );
+        }
+
+        Ok(message)
+    }
+
+    /// Sends a message to Twitch chat, if the message is over the character limit
+    /// the message will be chunked into multiple parts and sent separately
+    pub async fn send_chat_message_chunked(&self, message: &str) -> anyhow::Result<()> {
+        // Twitch counts the limit in characters rather than bytes
+        if message.chars().count() <= MAX_CHAT_MESSAGE_LENGTH {
+            self.send_chat_message(message).await?;
+        } else {
+            for chunk in chunk_chars(message, MAX_CHAT_MESSAGE_LENGTH) {
+                self.send_chat_message(&chunk).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Posts the configured social links to chat, links are split across
+    /// multiple messages when they exceed the chat message limit
+    pub async fn post_socials(&self) -> anyhow::Result<()> {
+        let parts: Vec<String> = self
+            .properties
+            .borrow()
+            .socials
+            .iter()
+            .map(|social| format!("{}: {}", social.name, social.url))
+            .collect();
+
+        if parts.is_empty() {
+            anyhow::bail!("no social links configured");
+        }
+
+        for message in join_chunked(&parts, " | ", MAX_CHAT_MESSAGE_LENGTH) {
+            self.send_chat_message(&message).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Toggles lurk mode, entering lurk mode posts the lurk message and
+    /// slows polling, leaving it resumes polling unless idle was set from
+    /// the inspector. Returns whether lurk mode is now active
+    pub async fn toggle_lurk_mode(&self, message: &str) -> anyhow::Result<bool> {
+        if self.lurking.get() {
+            self.lurking.set(false);
+            return Ok(false);
+        }
+
+        self.send_chat_message(message).await?;
+        self.lurking.set(true);
+        Ok(true)
+    }
+
+    /// Marks a warned chat clear as pending, returns false when one is
+    /// already pending
+    pub fn begin_pending_clear_chat(&self) -> bool {
+        !self.clear_chat_pending.replace(true)
+    }
+
+    pub fn end_pending_clear_chat(&self) {
+        self.clear_chat_pending.set(false);
+    }
+
+    pub async fn clear_chat(&self) -> anyhow::Result<DeleteChatMessagesResponse> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = DeleteChatMessagesRequest::new(user_id.clone(), user_id);
+        let response: DeleteChatMessagesResponse =
+            self.helix_client.req_delete(request, &token).await?.data;
+
+        Ok(response)
+    }
+
+    /// Deletes a single chat message from the authenticated users channel
+    pub async fn delete_message(&self, message_id: &str) -> anyhow::Result<()> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request =
+            DeleteChatMessagesRequest::new(user_id.clone(), user_id).message_id(message_id);
+        self.helix_client.req_delete(request, &token).await?;
+        Ok(())
+    }
+
+    pub async fn create_clip(&self) -> anyhow::Result<Vec<CreatedClip>> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let response: Vec<CreatedClip> = self
+            .req_with_retry(|| {
+                let request =
+                    CreateClipRequestFixed(CreateClipRequest::broadcaster_id(user_id.clone()));
+                self.helix_client.req_post(request, EmptyBody, &token)
+            })
+            .await?
+            .data;
+
+        Ok(response)
+    }
+
+    /// Polls twitch until the clip is available or the timeout is reached
+    pub async fn wait_for_clip_ready(
+        &self,
+        clip_id: ClipId,
+        timeout: Duration,
+    ) -> anyhow::Result<Clip> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let started = tokio::time::Instant::now();
+
+        loop {
+            let request = GetClipsRequest::clip_ids(vec![clip_id.clone()]);
+            let response = self.helix_client.req_get(request, &token).await?.data;
+
+            if let Some(clip) = response.into_iter().next() {
+                return Ok(clip);
+            }
+
+            if started.elapsed() >= timeout {
+                anyhow::bail!("clip did not become ready within {}s", timeout.as_secs());
+            }
+
+            sleep(CLIP_READY_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Creates a clip and once it has finished processing posts the clip
+    /// to chat using the message template (`{url}` is the clip URL)
+    ///
+    /// Waiting for the clip and posting it happens in the background, the
+    /// created clip is returned immediately
+    pub async fn clip_and_post(
+        self: &Rc<Self>,
+        delay: Duration,
+        template: String,
+    ) -> anyhow::Result<CreatedClip> {
+        let clip = self
+            .create_clip()
+            .await?
+            .into_iter()
+            .next()
+            .context("clip creation returned no clip")?;
+
+        let state = self.clone();
+        let clip_id = clip.id.clone();
+
+        spawn_local(async move {
+            if let Err(error) = state.post_clip_when_ready(clip_id, delay, &template).await {
+                tracing::error!(?error, "clip and post: failed to post clip");
+            }
+        });
+
+        Ok(clip)
+    }
+
+    /// Waits for the clip to finish processing then posts it to chat
+    async fn post_clip_when_ready(
+        &self,
+        clip_id: ClipId,
+        delay: Duration,
+        template: &str,
+    ) -> anyhow::Result<()> {
+        // Give twitch time to process the clip before polling
+        sleep(delay).await;
+
+        let clip = self
+            .wait_for_clip_ready(clip_id, Duration::from_secs(30))
+            .await?;
+
+        let mut values = self.template_values();
+        values.push(("url", clip.url));
+
+        self.send_chat_message(&expand_template(template, &values))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_marker(&self, description: String) -> anyhow::Result<CreatedStreamMarker> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = CreateStreamMarkerRequest::new();
+        let body = CreateStreamMarkerBody::new(user_id, description);
+        let response: CreatedStreamMarker = self
+            .helix_client
+            .req_post(request, body, &token)
+            .await?
+            .data;
+
+        Ok(response)
+    }
+
+    /// Captures the current moment, creates a clip and a marker sharing the
+    /// same label and records both in the local marker log
+    pub async fn capture_moment(&self, label: String) -> anyhow::Result<()> {
+        let created_at = Timestamp::now();
+        let (clip, marker) = tokio::join!(self.create_clip(), self.create_marker(label.clone()));
+
+        let clip_url = match &clip {
+            Ok(clips) => clips.first().map(|clip| clip.edit_url.clone()),
+            Err(error) => {
+                tracing::error!(?error, "capture moment: failed to create clip");
+                None
+            }
+        };
+
+        let position_seconds = match &marker {
+            Ok(marker) => Some(marker.position_seconds),
+            Err(error) => {
+                tracing::error!(?error, "capture moment: failed to create marker");
+                None
+            }
+        };
+
+        if clip.is_err() && marker.is_err() {
+            anyhow::bail!("failed to create both clip and marker");
+        }
+
+        self.push_marker_log(MarkerLogEntry {
+            label,
+            created_at,
+            position_seconds,
+            clip_url,
+        })
+        .await;
+
+        match (clip, marker) {
+            (Err(error), _) => Err(error.context("created marker but failed to create clip")),
+            (_, Err(error)) => Err(error.context("created clip but failed to create marker")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Gets the title and start time of the next scheduled stream that has not
+    /// been canceled, [None] when nothing is scheduled
+    pub async fn get_next_scheduled_stream(&self) -> anyhow::Result<Option<(String, Timestamp)>> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = GetChannelStreamScheduleRequest::broadcaster_id(user_id);
+
+        let schedule = match self.helix_client.req_get(request, &token).await {
+            Ok(response) => response.data,
+            // Twitch responds with not found when the channel has no schedule
+            Err(ClientRequestError::HelixRequestGetError(HelixRequestGetError::Error {
+                status,
+                ..
+            })) if status.as_u16() == 404 => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        let now = Timestamp::now();
+        let segment = schedule
+            .segments
+            .into_iter()
+            .find(|segment| segment.canceled_until.is_none() && now.is_before(&segment.start_time));
+
+        Ok(segment.map(|segment| (segment.title, segment.start_time)))
+    }
+
+    /// Posts the time until the next scheduled stream to chat, returns false
+    /// when there is no upcoming stream
+    pub async fn post_next_stream(&self, template: &str) -> anyhow::Result<bool> {
+        let (title, start_time) = match self.get_next_scheduled_stream().await? {
+            Some(value) => value,
+            None => return Ok(false),
+        };
+
+        let until = timestamp_seconds(&start_time)
+            .zip(timestamp_seconds(&Timestamp::now()))
+            .map(|(start, now)| start - now)
+            .unwrap_or_default();
+
+        let mut values = self.template_values();
+        values.push(("title", title));
+        values.push(("time", format_compact_duration(until)));
+
+        let message = expand_template(template, &values);
+        self.send_chat_message(&message).await?;
+        Ok(true)
+    }
+
+    /// Creates a marker describing the current viewer count and stream uptime
+    /// (e.g "Viewers: 1234 @ 2h15m"), returns [None] when the stream is offline
+    pub async fn create_viewer_count_marker(&self) -> anyhow::Result<Option<CreatedStreamMarker>> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = GetStreamsRequest::user_ids(vec![user_id]).first(1);
+
+        let stream = match self
+            .helix_client
+            .req_get(request, &token)
+            .await?
+            .data
+            .into_iter()
+            .next()
+        {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        let uptime = timestamp_seconds(&Timestamp::now())
+            .zip(timestamp_seconds(&stream.started_at))
+            .map(|(now, started_at)| now - started_at)
+            .unwrap_or_default();
+
+        let description = format!(
+            "Viewers: {} @ {}",
+            stream.viewer_count,
+            format_compact_duration(uptime)
+        );
+
+        let marker = self.create_marker(description).await?;
+        Ok(Some(marker))
+    }
+
+    /// Posts the message to chat and creates a marker described by the same
+    /// message, the marker description is truncated to the twitch limit
+    pub async fn send_marked_message(&self, message: &str) -> anyhow::Result<()> {
+        let description: String = message
+            .chars()
+            .take(MAX_MARKER_DESCRIPTION_LENGTH)
+            .collect();
+        let (sent, marker) = tokio::join!(
+            self.send_chat_message_chunked(message),
+            self.create_marker(description)
+        );
+
+        match (sent, marker) {
+            (Err(message_error), Err(marker_error)) => {
+                tracing::error!(error = ?marker_error, "marked message: failed to create marker");
+                Err(message_error.context("failed to send message and create marker"))
+            }
+            (Err(error), Ok(_)) => Err(error.context("created marker but failed to send message")),
+            (Ok(_), Err(error)) => Err(error.context("sent message but failed to create marker")),
+            (Ok(_), Ok(marker)) => {
+                tracing::debug!(id = %marker.id, "created marker for message");
+                Ok(())
+            }
+        }
+    }
+
+    /// Records a marker in the session log and appends it to the log file
+    pub async fn push_marker_log(&self, entry: MarkerLogEntry) {
+        if let Err(error) = append_marker_log(Path::new(MARKER_LOG_FILE), &entry).await {
+            tracing::error!(?error, "failed to write marker log");
+        }
+
+        self.marker_log.borrow_mut().push(entry);
+    }
+
+    /// Exports the markers recorded this session as a chapters file, returns
+    /// the number of chapters written. Nothing is written when there are no
+    /// markers to export.
+    ///
+    /// Markers without a stream position are placed relative to the stream
+    /// start time, these are skipped when the stream is offline
+    pub async fn export_markers(
+        &self,
+        path: &Path,
+        format: ChapterFormat,
+    ) -> anyhow::Result<usize> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        let request = GetStreamsRequest::user_ids(vec![user_id]).first(1);
+        let stream = self
+            .helix_client
+            .req_get(request, &token)
+            .await?
+            .data
+            .into_iter()
+            .next();
+
+        let started_at = stream
+            .as_ref()
+            .and_then(|stream| timestamp_seconds(&stream.started_at));
+        let now = timestamp_seconds(&Timestamp::now());
+
+        let mut chapters: Vec<(i64, String)> = self
+            .marker_log
+            .borrow()
+            .iter()
+            .filter_map(|entry| {
+                let start = match entry.position_seconds {
+                    Some(value) => value,
+                    None => timestamp_seconds(&entry.created_at)? - started_at?,
+                };
+
+                Some((start, entry.label.clone()))
+            })
+            .collect();
+
+        chapters.sort_by_key(|(start, _)| *start);
+
+        let end_seconds = started_at
+            .zip(now)
+            .map(|(started_at, now)| now - started_at);
+        let chapters: Vec<(i64, &str)> = chapters
+            .iter()
+            .map(|(start, label)| (*start, label.as_str()))
+            .collect();
+
+        if chapters.is_empty() {
+            return Ok(0);
+        }
+
+        let contents = format_chapters(&chapters, format, end_seconds);
+        write_chapters(path, &contents)
+            .await
+            .with_context(|| format!("failed to write chapters to {}", path.display()))?;
+
+        Ok(chapters.len())
+    }
+
+    /// Advances the cycle for the provided key returning the index of the next
+    /// item from a collection of `len` items
+    pub fn next_cycle_index(&self, key: &str, len: usize) -> usize {
+        let indices = &mut *self.cycle_indices.borrow_mut();
+        let index = match indices.get(key) {
+            Some(current) => (current + 1) % len.max(1),
+            None => 0,
+        };
+
+        indices.insert(key.to_string(), index);
+        index
+    }
+
+    pub fn set_marker_category(&self, category: Option<String>) {
+        *self.marker_category.borrow_mut() = category;
+    }
+
+    /// Creates a marker prefixing the description with the selected
+    /// marker category when one is selected
+    pub async fn create_categorized_marker(
+        &self,
+        description: Option<String>,
+    ) -> anyhow::Result<CreatedStreamMarker> {
+        let category = self.marker_category.borrow().clone();
+        let description = match (category, description) {
+            (Some(category), Some(description)) => format!("{category}: {description}"),
+            (Some(category), None) => category,
+            (None, description) => description.unwrap_or_default(),
+        };
+
+        self.create_marker(description).await
+    }
+
+    pub async fn start_comercial(
+        &self,
+        length: CommercialLength,
+    ) -> anyhow::Result<Vec<StartCommercial>> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = StartCommercialRequest::new();
+        let body = StartCommercialBody::new(user_id, length);
+        let response = self
+            .helix_client
+            .req_post(request, body, &token)
+            .await?
+            .data;
+
+        Ok(response)
+    }
+
+    /// Pushes back the next scheduled ad break, the response contains the
+    /// remaining snooze count and the time of the next ad
+    pub async fn snooze_next_ad(&self) -> anyhow::Result<SnoozeNextAd> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let request = SnoozeNextAdRequest::broadcaster_id(token.user_id.clone());
+
+        match self.helix_client.req_post(request, EmptyBody, &token).await {
+            Ok(response) => response
+                .data
+                .into_iter()
+                .next()
+                .context("snooze response missing data"),
+            // Twitch responds with too many requests when no snoozes are left
+            Err(ClientRequestError::HelixRequestPostError(HelixRequestPostError::Error {
+                status,
+                ..
+            })) if status.as_u16() == 429 => {
+                tracing::warn!("cannot snooze ad, no snoozes left");
+                anyhow::bail!("no ad snoozes left")
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub async fn send_announcement(
+        &self,
+        message: &str,
+        color: AnnouncementColor,
+    ) -> anyhow::Result<()> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = SendChatAnnouncementRequest::new(user_id.clone(), user_id);
+        let body = SendChatAnnouncementBody::new(message, color)?;
+
+        _ = self.helix_client.req_post(request, body, &token).await?;
+        Ok(())
+    }
+
+    pub async fn get_channel_information(&self) -> anyhow::Result<ChannelInformation> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = GetChannelInformationRequest::broadcaster_ids(vec![user_id]);
+        let response = self.helix_client.req_get(request, &token).await?.data;
+        let channel = response
+            .into_iter()
+            .next()
+            .context("channel information not found")?;
+
+        Ok(channel)
+    }
+
+    pub fn current_channel_info(&self) -> Option<ChannelInformation> {
+        self.channel_info.borrow().clone()
+    }
+
+    /// Toggles a content classification label on the channel, returning
+    /// whether the label is now enabled
+    pub async fn toggle_content_classification(
+        &self,
+        label: ContentClassificationId,
+    ) -> anyhow::Result<bool> {
+        let channel = self.get_channel_information().await?;
+        let enabled = !channel.content_classification_labels.contains(&label);
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = ModifyChannelInformationRequest::broadcaster_id(user_id);
+        let mut body = ModifyChannelInformationBody::new();
+        body.content_classification_labels =
+            Some(vec![ContentClassificationLabel::new(label, enabled)]);
+
+        _ = self.helix_client.req_patch(request, body, &token).await?;
+        Ok(enabled)
+    }
+
+    /// Sets the stream title, titles over [MAX_TITLE_LENGTH] are rejected or
+    /// truncated depending on the configured [TitleOverflow] behavior
+    pub async fn set_stream_title(&self, title: &str) -> anyhow::Result<()> {
+        let overflow = self.properties.borrow().title_overflow;
+        let title = match fit_title(title, overflow) {
+            Ok(value) => value,
+            Err(length) => {
+                self.send_inspector(InspectorMessageOut::TitleTooLong {
+                    length,
+                    max: MAX_TITLE_LENGTH,
+                });
+                anyhow::bail!("title too long ({length}/{MAX_TITLE_LENGTH})");
+            }
+        };
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = ModifyChannelInformationRequest::broadcaster_id(user_id);
+        let mut body = ModifyChannelInformationBody::new();
+        body.title(title.as_ref());
+
+        _ = self.helix_client.req_patch(request, body, &token).await?;
+
+        // Reflect the new title on displays without waiting for the next update
+        if let Some(channel) = self.channel_info.borrow_mut().as_mut() {
+            channel.title = title.to_string();
+        }
+
+        Ok(())
+    }
+
+    /// Sets the stream title from a template, `{date}` is expanded to the
+    /// current (UTC) date using the provided date format
+    pub async fn set_dated_title(&self, template: &str, date_format: &str) -> anyhow::Result<()> {
+        // Custom date takes priority over the default template values
+        let mut values = vec![("date", format_date(&Timestamp::now(), date_format))];
+        values.extend(self.template_values());
+
+        let title = expand_template(template, &values);
+        self.set_stream_title(&title).await
+    }
+
+    /// Sets the stream category, the category is looked up by name preferring
+    /// an exact match over the first search result
+    pub async fn set_stream_category(&self, name: &str) -> anyhow::Result<()> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        let request = SearchCategoriesRequest::query(name);
+        let categories = self.helix_client.req_get(request, &token).await?.data;
+        let category = match categories
+            .iter()
+            .find(|category| category.name.eq_ignore_ascii_case(name))
+            .or_else(|| categories.first())
+        {
+            Some(value) => value,
+            None => {
+                tracing::warn!(%name, "category not found");
+                anyhow::bail!("category \"{name}\" not found");
+            }
+        };
+
+        let request = ModifyChannelInformationRequest::broadcaster_id(user_id);
+        let mut body = ModifyChannelInformationBody::new();
+        body.game_id(category.id.clone());
+
+        _ = self.helix_client.req_patch(request, body, &token).await?;
+        Ok(())
+    }
+
+    /// Pre-stream setup, updates the title and category then posts an announcement.
+    ///
+    /// Each step is optional and a failing step does not prevent the remaining
+    /// steps from running, the returned error lists every step that failed
+    pub async fn go_live(
+        &self,
+        title: Option<&str>,
+        category: Option<&str>,
+        announcement: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let mut failed = Vec::new();
+
+        if let Some(title) = title {
+            match self.set_stream_title(title).await {
+                Ok(_) => tracing::debug!("go live: updated title"),
+                Err(error) => {
+                    tracing::error!(?error, "go live: failed to set title");
+                    failed.push("title");
+                }
+            }
+        }
+
+        if let Some(category) = category {
+            match self.set_stream_category(category).await {
+                Ok(_) => tracing::debug!("go live: updated category"),
+                Err(error) => {
+                    tracing::error!(?error, "go live: failed to set category");
+                    failed.push("category");
+                }
+            }
+        }
+
+        if let Some(announcement) = announcement {
+            match self
+                .send_announcement(announcement, AnnouncementColor::Primary)
+                .await
+            {
+                Ok(_) => tracing::debug!("go live: sent announcement"),
+                Err(error) => {
+                    tracing::error!(?error, "go live: failed to send announcement");
+                    failed.push("announcement");
+                }
+            }
+        }
+
+        if !failed.is_empty() {
+            anyhow::bail!("go live steps failed: {}", failed.join(", "));
+        }
+
+        Ok(())
+    }
+
+    pub async fn set_chat_color(&self, color: NamedUserColor<'static>) -> anyhow::Result<()> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = UpdateUserChatColorRequest::new(user_id, color);
+
+        _ = self
+            .helix_client
+            .req_put(request, EmptyBody, &token)
+            .await?;
+        Ok(())
+    }
+
+    /// Swaps the chat color between the two provided colors, returning
+    /// the color that is now active
+    pub async fn toggle_chat_color(
+        &self,
+        first: NamedUserColor<'static>,
+        second: NamedUserColor<'static>,
+    ) -> anyhow::Result<NamedUserColor<'static>> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        let is_first_active = self
+            .chat_colors
+            .borrow()
+            .get(&user_id)
+            .is_some_and(|color| color.eq(&first));
+
+        let color = if is_first_active { second } else { first };
+
+        self.set_chat_color(color.clone()).await?;
+        self.chat_colors.borrow_mut().insert(user_id, color.clone());
+
+        Ok(color)
+    }
+
+    pub async fn get_chat_settings(&self) -> anyhow::Result<ChatSettings> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = GetChatSettingsRequest::broadcaster_id(user_id.clone());
+        let response: ChatSettings = self.helix_client.req_get(request, &token).await?.data;
+        Ok(response)
+    }
+
+    /// Current chat settings generation, capture this after applying settings
+    /// that will be restored later
+    pub fn settings_generation(&self) -> u64 {
+        self.settings_generation.get()
+    }
+
+    /// Marks the chat settings as changed, cancelling any pending restores
+    pub fn bump_settings_generation(&self) {
+        self.settings_generation
+            .set(self.settings_generation.get().wrapping_add(1));
+    }
+
+    /// Whether the chat settings have been changed since the generation was captured
+    pub fn is_settings_generation_current(&self, generation: u64) -> bool {
+        self.settings_generation.get() == generation
+    }
+
+    /// Updates the chat settings of the provided broadcaster, the authenticated
+    /// user must be the broadcaster or one of their moderators
+    pub async fn update_chat_settings(
+        &self,
+        broadcaster_id: UserId,
+        body: UpdateChatSettingsBody,
+    ) -> anyhow::Result<()> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = UpdateChatSettingsRequest::new(broadcaster_id, user_id);
+
+        _ = self
+            .req_with_retry(|| {
+                self.helix_client
+                    .req_patch(request.clone(), body.clone(), &token)
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Applies the chat settings to the authenticated users channel along with
+    /// any additional channels (by login) that the user moderates.
+    ///
+    /// Every channel is attempted, the returned error lists the channels that failed
+    pub async fn apply_chat_settings(
+        &self,
+        body: UpdateChatSettingsBody,
+        channels: &[String],
+    ) -> anyhow::Result<()> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        self.update_chat_settings(user_id, body.clone()).await?;
+
+        let mut failed = Vec::new();
+
+        for channel in channels {
+            let result = match self.get_user_id_by_login(channel).await {
+                Ok(broadcaster_id) => {
+                    self.update_chat_settings(broadcaster_id, body.clone())
+                        .await
+                }
+                Err(error) => Err(error),
+            };
+
+            match result {
+                Ok(_) => tracing::debug!(?channel, "applied chat settings to channel"),
+                Err(error) => {
+                    tracing::error!(?error, ?channel, "failed to apply chat settings to channel");
+                    failed.push(channel.as_str());
+                }
+            }
+        }
+
+        if !failed.is_empty() {
+            anyhow::bail!("failed to apply chat settings to: {}", failed.join(", "));
+        }
+
+        Ok(())
+    }
+
+    pub async fn toggle_slow_mode(&self, channels: &[String]) -> anyhow::Result<()> {
+        let settings = self.get_chat_settings().await?;
+        let mut body = UpdateChatSettingsBody::default();
+        body.slow_mode = Some(!settings.slow_mode);
+
+        self.bump_settings_generation();
+        self.apply_chat_settings(body, channels).await
+    }
+
+    pub async fn toggle_emote_only(&self, channels: &[String]) -> anyhow::Result<()> {
+        let settings = self.get_chat_settings().await?;
+        let mut body = UpdateChatSettingsBody::default();
+        body.emote_mode = Some(!settings.emote_mode);
+
+        self.bump_settings_generation();
+        self.apply_chat_settings(body, channels).await
+    }
+
+    pub async fn toggle_unique_chat(&self) -> anyhow::Result<()> {
+        let settings = self.get_chat_settings().await?;
+        let mut body = UpdateChatSettingsBody::default();
+        body.unique_chat_mode = Some(!settings.unique_chat_mode);
+
+        self.bump_settings_generation();
+        self.apply_chat_settings(body, &[]).await
+    }
+
+    /// Sets emote only, sub only, follower only and slow mode together in a
+    /// single update, used to lock down chat during raids
+    pub async fn set_lockdown(&self, enabled: bool) -> anyhow::Result<()> {
+        let mut body = UpdateChatSettingsBody::default();
+        body.emote_mode = Some(enabled);
+        body.subscriber_mode = Some(enabled);
+        body.follower_mode = Some(enabled);
+        body.slow_mode = Some(enabled);
+
+        self.bump_settings_generation();
+        self.apply_chat_settings(body, &[]).await
+    }
+
+    pub async fn toggle_follower_only(&self, channels: &[String]) -> anyhow::Result<()> {
+        let settings = self.get_chat_settings().await?;
+        let mut body = UpdateChatSettingsBody::default();
+        body.follower_mode = Some(!settings.follower_mode);
+
+        self.bump_settings_generation();
+        self.apply_chat_settings(body, channels).await
+    }
+
+    pub async fn toggle_sub_only(&self, channels: &[String]) -> anyhow::Result<()> {
+        let settings = self.get_chat_settings().await?;
+        let mut body = UpdateChatSettingsBody::default();
+        body.subscriber_mode = Some(!settings.subscriber_mode);
+
+        self.bump_settings_generation();
+        self.apply_chat_settings(body, channels).await
+    }
+
+    /// Sets slow mode on the users channel, the wait time (seconds) is only
+    /// updated when provided and is clamped to the range twitch allows
+    pub async fn set_slow_mode(&self, enabled: bool, wait_time: Option<u64>) -> anyhow::Result<()> {
+        let mut body = UpdateChatSettingsBody::default();
+        body.slow_mode = Some(enabled);
+
+        if enabled {
+            body.slow_mode_wait_time =
+                wait_time.map(|value| value.clamp(MIN_SLOW_MODE_WAIT, MAX_SLOW_MODE_WAIT));
+        }
+
+        self.bump_settings_generation();
+        self.apply_chat_settings(body, &[]).await
+    }
+
+    /// Sets follower only mode on the users channel, the follow duration
+    /// (minutes) is only updated when provided and is clamped to the range
+    /// twitch allows
+    pub async fn set_follower_mode(
+        &self,
+        enabled: bool,
+        duration: Option<u64>,
+    ) -> anyhow::Result<()> {
+        let mut body = UpdateChatSettingsBody::default();
+        body.follower_mode = Some(enabled);
+
+        if enabled {
+            body.follower_mode_duration =
+                duration.map(|value| value.min(MAX_FOLLOWER_MODE_DURATION));
+        }
+
+        self.bump_settings_generation();
+        self.apply_chat_settings(body, &[]).await
+    }
+
+    /// Enables slow mode with the provided wait time (seconds) on the users
+    /// channel, returns the previous slow mode settings for restoring later
+    pub async fn apply_slow_mode(&self, wait_time: u64) -> anyhow::Result<SlowModeSettings> {
+        let settings = self.get_chat_settings().await?;
+        let previous = SlowModeSettings {
+            enabled: settings.slow_mode,
+            wait_time: settings.slow_mode_wait_time,
+        };
+
+        let mut body = UpdateChatSettingsBody::default();
+        body.slow_mode = Some(true);
+        body.slow_mode_wait_time = Some(wait_time.clamp(MIN_SLOW_MODE_WAIT, MAX_SLOW_MODE_WAIT));
+
+        self.bump_settings_generation();
+        self.apply_chat_settings(body, &[]).await?;
+        Ok(previous)
+    }
+
+    /// Restores slow mode settings previously replaced by [State::apply_slow_mode]
+    pub async fn restore_slow_mode(&self, settings: SlowModeSettings) -> anyhow::Result<()> {
+        let mut body = UpdateChatSettingsBody::default();
+        body.slow_mode = Some(settings.enabled);
+
+        if settings.enabled {
+            body.slow_mode_wait_time = settings.wait_time;
+        }
+
+        self.apply_chat_settings(body, &[]).await
+    }
+
+    /// Toggles focus mode, enabling sub only chat and posting the enter message
+    /// or restoring the previous sub only setting and posting the exit message.
+    /// Returns whether focus mode is now active
+    pub async fn toggle_focus_mode(
+        &self,
+        enter_message: &str,
+        exit_message: &str,
+    ) -> anyhow::Result<bool> {
+        match self.focus_mode.get() {
+            Some(previous) => {
+                // Sub only chat was already on before focus mode, leave it on
+                if !previous {
+                    self.set_chat_mode(ChatMode::SubOnly, false).await?;
+                }
+
+                self.focus_mode.set(None);
+                self.send_chat_message(exit_message).await?;
+                Ok(false)
+            }
+            None => {
+                let previous = self.set_chat_mode(ChatMode::SubOnly, true).await?;
+                self.focus_mode.set(Some(previous));
+                self.send_chat_message(enter_message).await?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Times out the user on the authenticated users channel, the duration is
+    /// clamped to the longest timeout twitch allows
+    pub async fn timeout_user(
+        &self,
+        login: &str,
+        duration: u32,
+        reason: Option<&str>,
+    ) -> anyhow::Result<()> {
+        self.ban_user_for(login, Some(duration.clamp(1, MAX_TIMEOUT_DURATION)), reason)
+            .await
+    }
+
+    /// Permanently bans the user from the authenticated users channel
+    pub async fn ban_user(&self, login: &str, reason: Option<&str>) -> anyhow::Result<()> {
+        self.ban_user_for(login, None, reason).await
+    }
+
+    /// Bans the user for the duration in seconds, permanently when [None]
+    async fn ban_user_for(
+        &self,
+        login: &str,
+        duration: Option<u32>,
+        reason: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let target_id = self.get_user_id_by_login(login).await?;
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = BanUserRequest::new(user_id.clone(), user_id);
+        let body = BanUserBody::new(target_id, reason.unwrap_or_default().to_string(), duration);
+
+        self.helix_client.req_post(request, body, &token).await?;
+        Ok(())
+    }
+
+    /// Starts a raid from the authenticated users channel to the channel
+    /// with the provided login
+    pub async fn start_raid(&self, login: &str) -> anyhow::Result<()> {
+        let target_id = self.get_user_id_by_login(login).await?;
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = StartARaidRequest::new(user_id, target_id);
+
+        match self.helix_client.req_post(request, EmptyBody, &token).await {
+            Ok(_) => Ok(()),
+            // Twitch responds with conflict while a raid countdown is already running
+            Err(ClientRequestError::HelixRequestPostError(HelixRequestPostError::Error {
+                status,
+                ..
+            })) if status.as_u16() == 409 => {
+                tracing::warn!(%login, "cannot start raid, a raid is already in progress");
+                anyhow::bail!("a raid is already in progress")
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Cancels the pending raid on the authenticated users channel
+    pub async fn cancel_raid(&self) -> anyhow::Result<()> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let request = CancelARaidRequest::broadcaster_id(token.user_id.clone());
+
+        match self.helix_client.req_delete(request, &token).await {
+            Ok(_) => Ok(()),
+            // Twitch responds with not found when there is no pending raid
+            Err(ClientRequestError::HelixRequestDeleteError(HelixRequestDeleteError::Error {
+                status,
+                ..
+            })) if status.as_u16() == 404 => anyhow::bail!("no raid in progress"),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Sends a shoutout for the channel with the provided login from the
+    /// authenticated users channel
+    pub async fn send_shoutout(&self, login: &str) -> anyhow::Result<()> {
+        let target_id = self.get_user_id_by_login(login).await?;
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = SendAShoutoutRequest::new(user_id.clone(), target_id, user_id);
+
+        match self.helix_client.req_post(request, EmptyBody, &token).await {
+            Ok(_) => Ok(()),
+            // Twitch responds with too many requests while either the global
+            // or the per channel shoutout cooldown is active
+            Err(ClientRequestError::HelixRequestPostError(HelixRequestPostError::Error {
+                status,
+                message,
+                ..
+            })) if status.as_u16() == 429 => {
+                if message.contains("same broadcaster") {
+                    tracing::warn!(%login, "shoutout skipped, channel was shouted out recently");
+                    anyhow::bail!("{login} was shouted out recently")
+                }
+
+                tracing::warn!(%login, "shoutout skipped, shoutout cooldown is active");
+                anyhow::bail!("shoutout cooldown is active")
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Warns the user on the authenticated users channel, the user has to
+    /// acknowledge the warning before they can chat again
+    pub async fn warn_user(&self, login: &str, reason: &str) -> anyhow::Result<()> {
+        let target_id = self.get_user_id_by_login(login).await?;
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = WarnChatUserRequest::new(user_id.clone(), user_id);
+        let body = WarnChatUserBody::new(target_id, reason);
+
+        self.helix_client.req_post(request, body, &token).await?;
+        Ok(())
+    }
+
+    /// Adds the user to the block list of the authenticated user, this is
+    /// account wide rather than specific to the channel
+    pub async fn block_user(&self, login: &str) -> anyhow::Result<()> {
+        let target_id = self.get_user_id_by_login(login).await?;
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let request = BlockUserRequest::block_user(target_id);
+        self.helix_client
+            .req_put(request, EmptyBody, &token)
+            .await
+            .with_context(|| format!("failed to add {login} to your personal block list"))?;
+        Ok(())
+    }
+
+    /// Removes the user from the block list of the authenticated user
+    pub async fn unblock_user(&self, login: &str) -> anyhow::Result<()> {
+        let target_id = self.get_user_id_by_login(login).await?;
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let request = UnblockUserRequest::unblock_user(target_id);
+        self.helix_client
+            .req_delete(request, &token)
+            .await
+            .with_context(|| format!("failed to remove {login} from your personal block list"))?;
+        Ok(())
+    }
+
+    /// Grants moderator to the user with the provided login on the
+    /// authenticated users channel
+    pub async fn add_moderator(&self, login: &str) -> anyhow::Result<()> {
+        let target_id = self.get_user_id_by_login(login).await?;
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let request = AddChannelModeratorRequest::new(token.user_id.clone(), target_id);
+
+        match self.helix_client.req_post(request, EmptyBody, &token).await {
+            Ok(_) => Ok(()),
+            // Twitch rejects modding a user who is currently a VIP
+            Err(ClientRequestError::HelixRequestPostError(HelixRequestPostError::Error {
+                status,
+                message,
+                ..
+            })) if status.as_u16() == 400 && message.to_ascii_lowercase().contains("vip") => {
+                tracing::warn!(%login, "cannot add moderator, user is a VIP");
+                anyhow::bail!("{login} is a VIP, remove their VIP before adding them as moderator")
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Removes moderator from the user with the provided login on the
+    /// authenticated users channel
+    pub async fn remove_moderator(&self, login: &str) -> anyhow::Result<()> {
+        let target_id = self.get_user_id_by_login(login).await?;
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let request = RemoveChannelModeratorRequest::new(token.user_id.clone(), target_id);
+        self.helix_client.req_delete(request, &token).await?;
+        Ok(())
+    }
+
+    /// Grants VIP to the user on the authenticated users channel
+    pub async fn add_vip(&self, user_id: UserId) -> anyhow::Result<()> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let broadcaster_id = token.user_id.clone();
+        let request = AddChannelVipRequest::new(broadcaster_id, user_id);
+
+        match self.helix_client.req_post(request, EmptyBody, &token).await {
+            Ok(_) => Ok(()),
+            // Twitch responds with conflict when every VIP slot is used
+            Err(ClientRequestError::HelixRequestPostError(HelixRequestPostError::Error {
+                status,
+                ..
+            })) if status.as_u16() == 409 => {
+                tracing::warn!("cannot add VIP, every VIP slot is in use");
+                anyhow::bail!("no VIP slots available")
+            }
+            // Twitch responds with unprocessable entity when the user is
+            // already a VIP or is a moderator
+            Err(ClientRequestError::HelixRequestPostError(HelixRequestPostError::Error {
+                status,
+                ..
+            })) if status.as_u16() == 422 => {
+                tracing::warn!("cannot add VIP, user is already a VIP or moderator");
+                anyhow::bail!("user is already a VIP or moderator")
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Removes VIP from the user on the authenticated users channel, any
+    /// pending temporary VIP removal for the user is cancelled
+    pub async fn remove_vip(&self, user_id: UserId) -> anyhow::Result<()> {
+        if let Some(task) = self.temp_vips.borrow_mut().remove(&user_id) {
+            task.abort();
+        }
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let broadcaster_id = token.user_id.clone();
+        let request = RemoveChannelVipRequest::new(broadcaster_id, user_id);
+        self.helix_client.req_delete(request, &token).await?;
+        Ok(())
+    }
+
+    /// Checks whether the user is a VIP on the authenticated users channel
+    ///
+    /// Only the first [MAX_VIP_PAGES] pages of VIPs are searched
+    pub async fn is_vip(&self, user_id: &UserId) -> anyhow::Result<bool> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let broadcaster_id = token.user_id.clone();
+        let request = GetChannelVipsRequest::broadcaster_id(broadcaster_id);
+        let mut response = self.helix_client.req_get(request, &token).await?;
+
+        for _ in 0..MAX_VIP_PAGES {
+            if response.data.iter().any(|vip| &vip.user_id == user_id) {
+                return Ok(true);
+            }
+
+            response = match response.get_next(&self.helix_client, &token).await? {
+                Some(value) => value,
+                None => break,
+            };
+        }
+
+        Ok(false)
+    }
+
+    /// Grants VIP to the user for the provided duration, granting VIP to the
+    /// same user again replaces the previously scheduled removal.
+    ///
+    /// The removal is skipped when the VIP was revoked manually in the meantime
+    /// and cancelled when VIP is removed through [State::remove_vip]
+    pub async fn grant_temp_vip(
+        self: &Rc<Self>,
+        login: &str,
+        duration: Duration,
+    ) -> anyhow::Result<()> {
+        let user_id = self.get_user_id_by_login(login).await?;
+        self.add_vip(user_id.clone()).await?;
+
+        let state = self.clone();
+        let removal_user_id = user_id.clone();
+        let login = login.to_string();
+
+        let task = spawn_local(async move {
+            sleep(duration).await;
+            state.temp_vips.borrow_mut().remove(&removal_user_id);
+
+            match state.is_vip(&removal_user_id).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::info!(%login, "temporary VIP already revoked");
+                    return;
+                }
+                Err(error) => {
+                    tracing::error!(?error, %login, "failed to check temporary VIP");
+                    return;
+                }
+            }
+
+            match state.remove_vip(removal_user_id).await {
+                Ok(_) => tracing::info!(%login, "removed temporary VIP"),
+                Err(error) => tracing::error!(?error, %login, "failed to remove temporary VIP"),
+            }
+        });
+
+        if let Some(previous) = self.temp_vips.borrow_mut().insert(user_id, task) {
+            previous.abort();
+        }
+
+        Ok(())
+    }
+
+    /// Sets the delay before messages from non moderators are shown in chat,
+    /// a delay of zero disables the chat delay
+    pub async fn set_chat_delay(&self, delay: u64) -> anyhow::Result<()> {
+        let mut body = UpdateChatSettingsBody::default();
+        body.non_moderator_chat_delay = Some(delay > 0);
+
+        if delay > 0 {
+            body.non_moderator_chat_delay_duration = Some(delay);
+        }
+
+        self.bump_settings_generation();
+        self.apply_chat_settings(body, &[]).await
+    }
+
+    /// Enables or disables a chat mode on the users channel, returns whether
+    /// the mode was previously enabled
+    pub async fn set_chat_mode(&self, mode: ChatMode, enabled: bool) -> anyhow::Result<bool> {
+        let settings = self.get_chat_settings().await?;
+        let previous = mode.is_enabled(&settings);
+
+        let mut body = UpdateChatSettingsBody::default();
+        mode.set_enabled(&mut body, enabled);
+
+        self.bump_settings_generation();
+        self.apply_chat_settings(body, &[]).await?;
+        Ok(previous)
+    }
+
+    /// Posts the active chat restrictions to chat using the template, returns
+    /// false when nothing was posted because no restrictions are active
+    pub async fn post_chat_status(
+        &self,
+        template: &str,
+        post_when_clear: bool,
+    ) -> anyhow::Result<bool> {
+        let settings = self.get_chat_settings().await?;
+        let restrictions = chat_restrictions(&settings);
+
+        let message = if restrictions.is_empty() {
+            if !post_when_clear {
+                return Ok(false);
+            }
+
+            "Chat has no restrictions".to_string()
+        } else {
+            let mut values = self.template_values();
+            values.push(("restrictions", restrictions.join(" + ")));
+            expand_template(template, &values)
+        };
+
+        self.send_chat_message(&message).await?;
+        Ok(true)
+    }
+
+    pub async fn get_view_count(&self) -> anyhow::Result<Option<usize>> {
+        let token = match self.get_user_token() {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let user_id = token.user_id.clone();
+        let request = GetStreamsRequest::user_ids(vec![user_id]).first(1);
+
+        let response = self.helix_client.req_get(request, &token).await?.data;
+        let view_count = response.first().map(|stream| stream.viewer_count);
+        Ok(view_count)
+    }
+
+    /// Finds the rank of the broadcaster by viewer count among the live streams
+    /// in the same category, returns the rank and the category name
+    ///
+    /// Only the first [MAX_RANK_SEARCH_PAGES] pages of streams are searched
+    pub async fn get_category_rank(&self) -> anyhow::Result<(usize, String)> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        let request = GetStreamsRequest::user_ids(vec![user_id.clone()]).first(1);
+        let response = self.helix_client.req_get(request, &token).await?.data;
+        let stream = response.into_iter().next().context("stream is not live")?;
+
+        let request = GetStreamsRequest::game_ids(vec![stream.game_id]).first(100);
+        let mut response = self.helix_client.req_get(request, &token).await?;
+        let mut offset = 0;
+
+        for _ in 0..MAX_RANK_SEARCH_PAGES {
+            if let Some(index) = response
+                .data
+                .iter()
+                .position(|other| other.user_id == user_id)
+            {
+                return Ok((offset + index + 1, stream.game_name));
+            }
+
+            offset += response.data.len();
+            response = match response.get_next(&self.helix_client, &token).await? {
+                Some(value) => value,
+                None => break,
+            };
+        }
+
+        anyhow::bail!(
+            "stream not found within the top {offset} streams of {}",
+            stream.game_name
+        )
+    }
+
+    /// Sums the viewers across the live streams in the broadcasters category,
+    /// returns the total and the broadcasters share of the total as a percentage.
+    /// [None] when the stream is offline
+    ///
+    /// Only the first [MAX_CATEGORY_VIEWER_PAGES] pages of streams are counted
+    pub async fn get_category_viewers(&self) -> anyhow::Result<Option<(usize, f64)>> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        let request = GetStreamsRequest::user_ids(vec![user_id]).first(1);
+        let response = self.helix_client.req_get(request, &token).await?.data;
+        let stream = match response.into_iter().next() {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        let request = GetStreamsRequest::game_ids(vec![stream.game_id]).first(100);
+        let mut response = self.helix_client.req_get(request, &token).await?;
+        let mut total = 0;
+
+        for _ in 0..MAX_CATEGORY_VIEWER_PAGES {
+            total += response
+                .data
+                .iter()
+                .map(|stream| stream.viewer_count)
+                .sum::<usize>();
+
+            response = match response.get_next(&self.helix_client, &token).await? {
+                Some(value) => value,
+                None => break,
+            };
+        }
+
+        let share = if total > 0 {
+            stream.viewer_count as f64 / total as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        Ok(Some((total, share)))
+    }
+
+    pub fn current_category_viewers(&self) -> Option<(usize, f64)> {
+        self.category_viewers.get()
+    }
+
+    /// Gets the total number of followers of the authenticated users channel
+    pub async fn get_follower_count(&self) -> anyhow::Result<u64> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        let request = GetChannelFollowersRequest::broadcaster_id(user_id);
+        let response = self.helix_client.req_get(request, &token).await?;
+        let total = response.total.context("missing follower total")?;
+
+        Ok(total.max(0) as u64)
+    }
+
+    pub fn current_follower_count(&self) -> Option<u64> {
+        self.follower_count.get()
+    }
+
+    /// Gets the time the current stream started (seconds since the unix
+    /// epoch), [None] when the stream is offline
+    pub async fn get_stream_started_at(&self) -> anyhow::Result<Option<i64>> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let request = GetStreamsRequest::user_ids(vec![user_id]).first(1);
+
+        let response = self.helix_client.req_get(request, &token).await?.data;
+        Ok(response
+            .first()
+            .and_then(|stream| timestamp_seconds(&stream.started_at)))
+    }
+
+    /// Seconds since the current stream started, [None] while offline
+    pub fn current_uptime(&self) -> Option<u64> {
+        let started_at = self.stream_started_at.get()?;
+        let now = timestamp_seconds(&Timestamp::now())?;
+        Some(uptime_seconds(started_at, now))
+    }
+
+    /// Announces the broadcasters rank within their category in chat
+    pub async fn post_category_rank(&self) -> anyhow::Result<usize> {
+        let (rank, game) = self.get_category_rank().await?;
+        self.send_chat_message(&format!("We're #{rank} in {game}!"))
+            .await?;
+        Ok(rank)
+    }
+
+    /// Posts the change in category rank since the last check to chat, the
+    /// first check (or a check after changing category) acts as a baseline
+    pub async fn post_category_rank_change(&self) -> anyhow::Result<usize> {
+        let (rank, game) = self.get_category_rank().await?;
+        let previous = self.last_rank.replace(Some((rank, game.clone())));
+
+        let message = match previous {
+            Some((previous, previous_game)) if previous_game == game => {
+                if rank < previous {
+                    let spots = previous - rank;
+                    let unit = if spots == 1 { "spot" } else { "spots" };
+                    format!("Moved up {spots} {unit} to #{rank} in {game}!")
+                } else if rank > previous {
+                    let spots = rank - previous;
+                    let unit = if spots == 1 { "spot" } else { "spots" };
+                    format!("Dropped {spots} {unit} to #{rank} in {game}")
+                } else {
+                    format!("Holding steady at #{rank} in {game}")
+                }
+            }
+            _ => format!("We're #{rank} in {game}!"),
+        };
+
+        self.send_chat_message(&message).await?;
+        Ok(rank)
+    }
+
+    /// Finds the currently active prediction (or poll when there is no
+    /// prediction) returning its title and the standings of each option
+    pub async fn get_interaction_standings(&self) -> anyhow::Result<Option<(String, String)>> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        let request = GetPredictionsRequest::broadcaster_id(user_id.clone());
+        let predictions: Vec<_> = self.helix_client.req_get(request, &token).await?.data;
+
+        let prediction = predictions.into_iter().find(|prediction| {
+            matches!(
+                prediction.status,
+                PredictionStatus::Active | PredictionStatus::Locked
+            )
+        });
+
+        if let Some(prediction) = prediction {
+            let options: Vec<(&str, i64)> = prediction
+                .outcomes
+                .iter()
+                .map(|outcome| {
+                    (
+                        outcome.title.as_str(),
+                        outcome.channel_points.unwrap_or_default(),
+                    )
+                })
+                .collect();
+
+            return Ok(Some((prediction.title.clone(), format_standings(&options))));
+        }
+
+        let request = GetPollsRequest::broadcaster_id(user_id);
+        let polls: Vec<_> = self.helix_client.req_get(request, &token).await?.data;
+
+        let poll = polls
+            .into_iter()
+            .find(|poll| matches!(poll.status, PollStatus::Active));
+
+        Ok(poll.map(|poll| {
+            let options: Vec<(&str, i64)> = poll
+                .choices
+                .iter()
+                .map(|choice| (choice.title.as_str(), choice.votes.unwrap_or_default()))
+                .collect();
+
+            (poll.title.clone(), format_standings(&options))
+        }))
+    }
+
+    /// Creates a poll on the authenticated users channel, the created poll is
+    /// remembered so it can be ended later
+    pub async fn create_poll(
+        &self,
+        title: &str,
+        choices: &[String],
+        duration: u32,
+    ) -> anyhow::Result<PollId> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        let choices = choices
+            .iter()
+            .map(|choice| NewPollChoice::new(choice.as_str()))
+            .collect();
+        let request = CreatePollRequest::new();
+        let body = CreatePollBody::new(user_id, title, duration as i64, choices);
+
+        let poll = self
+            .helix_client
+            .req_post(request, body, &token)
+            .await?
+            .data;
+        *self.last_poll.borrow_mut() = Some(poll.id.clone());
+        Ok(poll.id)
+    }
+
+    /// Ends the most recent poll created by the plugin, falls back to the
+    /// active poll on the channel when no poll was created by the plugin.
+    ///
+    /// Returns false when there is no poll to end
+    pub async fn end_poll(&self, status: PollStatus) -> anyhow::Result<bool> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        // Only forgotten once ended so the poll can be ended again on failure
+        let last_poll = self.last_poll.borrow().clone();
+        let poll_id = match last_poll {
+            Some(value) => value,
+            None => {
+                let request = GetPollsRequest::broadcaster_id(user_id.clone());
+                let polls: Vec<_> = self.helix_client.req_get(request, &token).await?.data;
+                match polls
+                    .into_iter()
+                    .find(|poll| matches!(poll.status, PollStatus::Active))
+                {
+                    Some(poll) => poll.id,
+                    None => return Ok(false),
+                }
+            }
+        };
+
+        let request = EndPollRequest::new();
+        let body = EndPollBody::new(user_id, poll_id.clone(), status);
+        _ = self.helix_client.req_patch(request, body, &token).await?;
+
+        // Another poll may have been created while ending this one
+        let last_poll = &mut *self.last_poll.borrow_mut();
+        if last_poll.as_ref() == Some(&poll_id) {
+            *last_poll = None;
+        }
+
+        Ok(true)
+    }
+
+    /// Creates a prediction on the authenticated users channel, the created
+    /// prediction is remembered so it can be resolved later
+    pub async fn create_prediction(
+        &self,
+        title: &str,
+        outcomes: &[String],
+        window: u32,
+    ) -> anyhow::Result<PredictionId> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        let outcomes = outcomes
+            .iter()
+            .map(|outcome| NewPredictionOutcome::new(outcome.as_str()))
+            .collect();
+        let request = CreatePredictionRequest::new();
+        let body = CreatePredictionBody::new(user_id, title, outcomes, window as i64);
+
+        let prediction = self
+            .helix_client
+            .req_post(request, body, &token)
+            .await?
+            .data;
+        let id = prediction.id.clone();
+        *self.last_prediction.borrow_mut() = Some(prediction);
+        Ok(id)
+    }
+
+    /// Locks, resolves or cancels the most recent prediction created by the
+    /// plugin, falls back to the unresolved prediction on the channel when no
+    /// prediction was created by the plugin.
+    ///
+    /// `winning_index` is the index of the winning outcome, required when
+    /// resolving. Returns false when there is no prediction to end
+    pub async fn resolve_prediction(
+        &self,
+        status: PredictionStatus,
+        winning_index: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        let prediction = self.last_prediction.borrow().clone();
+        let prediction = match prediction {
+            Some(value) => value,
+            None => {
+                let request = GetPredictionsRequest::broadcaster_id(user_id.clone());
+                let predictions: Vec<_> = self.helix_client.req_get(request, &token).await?.data;
+                match predictions.into_iter().find(|prediction| {
+                    matches!(
+                        prediction.status,
+                        PredictionStatus::Active | PredictionStatus::Locked
+                    )
+                }) {
+                    Some(value) => value,
+                    None => return Ok(false),
+                }
+            }
+        };
+
+        let request = EndPredictionRequest::new();
+        let mut body = EndPredictionBody::new(user_id, prediction.id.clone(), status.clone());
+
+        if matches!(status, PredictionStatus::Resolved) {
+            let index = winning_index.context("missing winning outcome")?;
+            let outcome = prediction.outcomes.get(index).with_context(|| {
+                format!(
+                    "winning outcome {index} out of range, prediction has {} outcomes",
+                    prediction.outcomes.len()
+                )
+            })?;
+            body = body.winning_outcome_id(outcome.id.as_str());
+        }
+
+        _ = self.helix_client.req_patch(request, body, &token).await?;
+
+        // Locked predictions still need to be resolved
+        if !matches!(status, PredictionStatus::Locked) {
+            *self.last_prediction.borrow_mut() = None;
+        }
+
+        Ok(true)
+    }
+
+    /// Counts the active polls and predictions on the channel, locked
+    /// predictions are counted as they still need to be resolved
+    pub async fn get_active_interactions(&self) -> anyhow::Result<(usize, usize)> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        let request = GetPollsRequest::broadcaster_id(user_id.clone());
+        let polls: Vec<_> = self.helix_client.req_get(request, &token).await?.data;
+        let polls = polls
+            .iter()
+            .filter(|poll| matches!(poll.status, PollStatus::Active))
+            .count();
+
+        let request = GetPredictionsRequest::broadcaster_id(user_id);
+        let predictions: Vec<_> = self.helix_client.req_get(request, &token).await?.data;
+        let predictions = predictions
+            .iter()
+            .filter(|prediction| {
+                matches!(
+                    prediction.status,
+                    PredictionStatus::Active | PredictionStatus::Locked
+                )
+            })
+            .count();
+
+        Ok((polls, predictions))
+    }
+
+    pub fn current_active_interactions(&self) -> (usize, usize) {
+        self.active_interactions.get()
+    }
+
+    /// Posts the standings of the active prediction or poll to chat using
+    /// the provided template, returns false when nothing is active
+    pub async fn post_interaction_standings(&self, template: &str) -> anyhow::Result<bool> {
+        let (title, standings) = match self.get_interaction_standings().await? {
+            Some(value) => value,
+            None => return Ok(false),
+        };
+
+        let mut values = self.template_values();
+        values.push(("title", title));
+        values.push(("standings", standings));
+
+        let message = expand_template(template, &values);
+        self.send_chat_message(&message).await?;
+        Ok(true)
+    }
+
+    /// Creates the EventSub subscriptions for the provided websocket session
+    pub async fn subscribe_eventsub(&self, session_id: &str) -> anyhow::Result<()> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let transport = Transport::websocket(session_id);
+
+        self.helix_client
+            .create_eventsub_subscription(
+                ChannelFollowV2::new(user_id.clone(), user_id.clone()),
+                transport.clone(),
+                &token,
+            )
+            .await
+            .context("failed to subscribe to channel follows")?;
+
+        self.helix_client
+            .create_eventsub_subscription(
+                StreamOnlineV1::broadcaster_user_id(user_id.clone()),
+                transport.clone(),
+                &token,
+            )
+            .await
+            .context("failed to subscribe to stream online")?;
+
+        self.helix_client
+            .create_eventsub_subscription(
+                ChannelSubscribeV1::broadcaster_user_id(user_id.clone()),
+                transport.clone(),
+                &token,
+            )
+            .await
+            .context("failed to subscribe to channel subscriptions")?;
+
+        self.helix_client
+            .create_eventsub_subscription(
+                ChannelRaidV1::to_broadcaster_user_id(user_id),
+                transport,
+                &token,
+            )
+            .await
+            .context("failed to subscribe to incoming raids")?;
+
+        Ok(())
+    }
+
+    pub fn increment_followers_gained(&self) {
+        self.followers_gained.set(self.followers_gained.get() + 1);
+    }
+
+    pub fn reset_followers_gained(&self) {
+        self.followers_gained.set(0);
+    }
+
+    /// Marks the stream as live so the view count is polled at the
+    /// regular interval again
+    pub fn set_stream_online(&self) {
+        self.stream_offline.set(false);
+    }
+
+    pub fn set_latest_follower(&self, login: String) {
+        *self.latest_follower.borrow_mut() = Some(login);
+    }
+
+    pub fn set_latest_subscriber(&self, login: String) {
+        *self.latest_subscriber.borrow_mut() = Some(login);
+    }
+
+    pub fn set_last_raid(&self, raid: IncomingRaid) {
+        *self.last_raid.borrow_mut() = Some(raid);
+    }
+
+    pub fn last_raid(&self) -> Option<IncomingRaid> {
+        self.last_raid.borrow().clone()
+    }
+
+    /// Thanks the most recent raid in chat, returns false when no raid
+    /// has been received within the [RECENT_RAID_WINDOW]
+    pub async fn post_raid_thanks(&self, template: &str) -> anyhow::Result<bool> {
+        let raid = match self
+            .last_raid()
+            .filter(|raid| raid.received_at.elapsed() < RECENT_RAID_WINDOW)
+        {
+            Some(value) => value,
+            None => return Ok(false),
+        };
+
+        let mut values = self.template_values();
+        values.push(("raider", raid.from_login));
+        values.push(("viewers", raid.viewers.to_string()));
+
+        let message = expand_template(template, &values);
+        self.send_chat_message(&message).await?;
+        Ok(true)
+    }
+
+    /// Applies the configured raid defense for the incoming raid, posting the
+    /// welcome message and enabling the chat mode for the configured window
+    /// before restoring it. Does nothing when auto raid defense is disabled
+    pub async fn apply_raid_defense(&self, raid: &IncomingRaid) -> anyhow::Result<()> {
+        let properties = self.get_properties();
+        if !properties.auto_raid_defense {
+            return Ok(());
+        }
+
+        let defense = properties.raid_defense;
+
+        if let Some(welcome) = defense.welcome.as_deref().filter(|value| !value.is_empty()) {
+            let mut values = self.template_values();
+            values.push(("raider", raid.from_login.clone()));
+            values.push(("viewers", raid.viewers.to_string()));
+
+            let message = expand_template(welcome, &values);
+            self.send_chat_message(&message).await?;
+        }
+
+        let mode = defense.mode.unwrap_or(ChatMode::FollowerOnly);
+        let previous = self.set_chat_mode(mode, true).await?;
+
+        self.send_inspector(InspectorMessageOut::RaidDefenseApplied {
+            raider: raid.from_login.clone(),
+            viewers: raid.viewers,
+        });
+
+        // Mode was already enabled, nothing to restore
+        if previous {
+            return Ok(());
+        }
+
+        let generation = self.settings_generation();
+        sleep(Duration::from_secs(
+            defense.duration.unwrap_or(DEFAULT_RAID_DEFENSE_DURATION),
+        ))
+        .await;
+
+        if !self.is_settings_generation_current(generation) {
+            tracing::info!("chat settings changed, skipping raid defense restore");
+            return Ok(());
+        }
+
+        self.set_chat_mode(mode, false).await?;
+        tracing::info!(?mode, "restored chat mode after raid defense");
+        Ok(())
+    }
+
+    pub fn latest_follower(&self) -> Option<String> {
+        self.latest_follower.borrow().clone()
+    }
+
+    pub fn latest_subscriber(&self) -> Option<String> {
+        self.latest_subscriber.borrow().clone()
+    }
+
+    /// Posts the number of followers gained this stream to chat, nothing
+    /// is posted when no followers have been gained
+    pub async fn post_followers_gained(&self) -> anyhow::Result<u64> {
+        let gained = self.followers_gained.get();
+        if gained == 0 {
+            tracing::info!("no followers gained this stream, skipping post");
+            return Ok(0);
+        }
+
+        let noun = if gained == 1 { "follower" } else { "followers" };
+        self.send_chat_message(&format!("We gained {gained} {noun} this stream!"))
+            .await?;
+
+        Ok(gained)
+    }
+
+    /// Gets the channel point rewards created by this plugins client ID (Twitch
+    /// only allows updating rewards created by the same client)
+    pub async fn get_rewards(&self, refresh: bool) -> anyhow::Result<Vec<RewardSummary>> {
+        if !refresh {
+            if let Some(rewards) = self.rewards.borrow().as_ref() {
+                return Ok(rewards.clone());
+            }
+        }
+
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let mut request = GetCustomRewardRequest::broadcaster_id(user_id);
+        request.only_manageable_rewards = true;
+
+        let response = self.helix_client.req_get(request, &token).await?.data;
+        let rewards: Vec<RewardSummary> = response
+            .into_iter()
+            .map(|reward| RewardSummary {
+                id: reward.id.to_string(),
+                title: reward.title,
+            })
+            .collect();
+
+        *self.rewards.borrow_mut() = Some(rewards.clone());
+        Ok(rewards)
+    }
+
+    /// Toggles whether redemptions of the reward skip the request queue (are
+    /// automatically fulfilled), returns the new setting. Only rewards created
+    /// by the plugin can be updated
+    pub async fn toggle_reward_auto_fulfill(&self, reward_id: RewardId) -> anyhow::Result<bool> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let mut request = GetCustomRewardRequest::broadcaster_id(user_id.clone());
+        request.only_manageable_rewards = true;
+
+        let response = self.helix_client.req_get(request, &token).await?.data;
+        let reward = response
+            .into_iter()
+            .find(|reward| reward.id == reward_id)
+            .context("reward not found or not manageable by the plugin")?;
 
-impl State {
-    pub fn set_inspector(&self, inspector: Option<Inspector>) {
-        *self.inspector.borrow_mut() = inspector;
+        let skip_queue = !reward.should_redemptions_skip_request_queue;
+
+        let request = UpdateCustomRewardRequest::new(user_id, reward_id);
+        let mut body = UpdateCustomRewardBody::default();
+        body.should_redemptions_skip_request_queue = Some(skip_queue);
+
+        _ = self.helix_client.req_patch(request, body, &token).await?;
+        Ok(skip_queue)
     }
 
-    pub fn set_logged_out(&self) {
-        let state = &mut *self.access_state.lock();
-        *state = AccessState::NotAuthenticate;
-        self.update_inspector();
+    /// Updates the status of the oldest unfulfilled redemptions for a reward,
+    /// returns the number of redemptions that were updated
+    pub async fn update_redemptions(
+        &self,
+        reward_id: RewardId,
+        status: CustomRewardRedemptionStatus,
+        count: usize,
+    ) -> anyhow::Result<usize> {
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+
+        // Redemptions are sorted oldest first by default
+        let request = GetCustomRewardRedemptionRequest::broadcaster_id(user_id.clone())
+            .reward_id(reward_id.clone())
+            .status(CustomRewardRedemptionStatus::Unfulfilled)
+            .first(count.clamp(1, 50));
+        let redemptions = self.helix_client.req_get(request, &token).await?.data;
+
+        if redemptions.is_empty() {
+            return Ok(0);
+        }
+
+        let ids: Vec<_> = redemptions
+            .into_iter()
+            .map(|redemption| redemption.id)
+            .collect();
+        let updated = ids.len();
+
+        let request = UpdateRedemptionStatusRequest::new(user_id, reward_id, ids);
+        let body = UpdateRedemptionStatusBody::status(status);
+
+        _ = self.helix_client.req_patch(request, body, &token).await?;
+        Ok(updated)
     }
 
-    pub fn update_inspector(&self) {
-        if let Some(inspector) = self.inspector.borrow().as_ref() {
-            let state = &*self.access_state.lock();
-            match state {
-                AccessState::NotAuthenticate => {
-                    _ = inspector.send(InspectorMessageOut::State {
-                        state: "NOT_AUTHENTICATED".to_string(),
-                    });
-                }
-                AccessState::Loading => {
-                    _ = inspector.send(InspectorMessageOut::State {
-                        state: "LOADING".to_string(),
-                    });
-                }
-                AccessState::Authenticated { .. } => {
-                    _ = inspector.send(InspectorMessageOut::State {
-                        state: "AUTHENTICATED".to_string(),
-                    });
-                }
+    /// Counts the unfulfilled redemptions across the rewards manageable
+    /// by the plugin
+    ///
+    /// Only the first [MAX_REDEMPTION_PAGES] pages of each reward are counted
+    pub async fn get_pending_redemptions(&self) -> anyhow::Result<usize> {
+        let rewards = self.get_rewards(false).await?;
+        let token = self.get_user_token().context("not authenticated")?;
+        let user_id = token.user_id.clone();
+        let mut count = 0;
+
+        for reward in rewards {
+            let request = GetCustomRewardRedemptionRequest::broadcaster_id(user_id.clone())
+                .reward_id(RewardId::from(reward.id))
+                .status(CustomRewardRedemptionStatus::Unfulfilled)
+                .first(50);
+            let mut response = self.helix_client.req_get(request, &token).await?;
+
+            for _ in 0..MAX_REDEMPTION_PAGES {
+                count += response.data.len();
+                response = match response.get_next(&self.helix_client, &token).await? {
+                    Some(value) => value,
+                    None => break,
+                };
             }
         }
+
+        Ok(count)
     }
 
-    pub async fn create_user_token(&self, access_token: AccessToken) -> anyhow::Result<UserToken> {
-        UserToken::from_existing(&self.helix_client, access_token, None, None)
-            .await
-            .context("failed to create user token")
+    pub fn current_pending_redemptions(&self) -> usize {
+        self.pending_redemptions.get()
     }
 
-    pub async fn attempt_auth(&self, access_token: AccessToken) -> anyhow::Result<()> {
-        {
-            let lock = &mut *self.access_state.lock();
-            *lock = AccessState::Loading;
-        }
+    /// Total number of active displays across every polled value
+    pub fn get_total_active_displays(&self) -> usize {
+        self.displays.active_count()
+    }
 
-        self.update_inspector();
+    pub fn current_view_count(&self) -> usize {
+        self.viewers.get()
+    }
 
-        // Create user token (Validates it with the twitch backend)
-        let user_token = self.create_user_token(access_token).await?;
+    /// View count used for checking action conditions, fetched when no poll
+    /// updated it recently (e.g no view count display is active). Zero while
+    /// the stream is offline
+    pub async fn condition_view_count(&self) -> usize {
+        let fresh = self
+            .api_latency
+            .get()
+            .is_some_and(|(_, measured_at)| measured_at.elapsed() < CONDITION_VIEW_COUNT_MAX_AGE);
 
-        {
-            let lock = &mut *self.access_state.lock();
-            *lock = AccessState::Authenticated { user_token };
+        if !fresh {
+            self.update_metric(Metric::ViewCount).await;
         }
 
-        self.update_inspector();
-
-        Ok(())
+        if self.stream_offline.get() {
+            0
+        } else {
+            self.viewers.get()
+        }
     }
 
-    pub fn get_user_token(&self) -> Option<UserToken> {
-        let lock = &*self.access_state.lock();
-        match lock {
-            AccessState::Authenticated { user_token } => Some(user_token.clone()),
-            _ => None,
-        }
+    /// Change in viewers since the previous poll, [None] while offline
+    pub fn current_view_count_delta(&self) -> Option<i64> {
+        self.viewers_delta.get()
     }
 
-    pub async fn send_chat_message(
+    pub fn push_active_display(
         &self,
-        message: &str,
-    ) -> anyhow::Result<SendChatMessageResponse> {
-        // Obtain twitch access token
-        let token = self.get_user_token().context("not authenticated")?;
+        display: &Display,
+        metric: Metric,
+        refresh_secs: Option<u64>,
+    ) {
+        self.displays.push(display, metric, refresh_secs);
+    }
 
-        // Get broadcaster user ID
-        let user_id = token.user_id.clone();
+    /// Interval the metric should be refreshed at
+    fn metric_interval(&self, metric: Metric) -> Duration {
+        let default = match metric {
+            Metric::ViewCount => self.view_count_interval(),
+            _ => metric.default_interval(),
+        };
 
-        // Create chat message request
-        let request = SendChatMessageRequest::new();
-        let body = SendChatMessageBody::new(user_id.clone(), user_id, message);
+        let mut interval = self.displays.refresh_interval(metric).unwrap_or(default);
 
-        // Send request and get response
-        let response: SendChatMessageResponse = self
-            .helix_client
-            .req_post(request, body, &token)
-            .await?
-            .data;
+        // Viewers can't change while offline, only check for the stream going live
+        if metric == Metric::ViewCount && self.stream_offline.get() {
+            interval = interval.max(OFFLINE_POLL_INTERVAL);
+        }
 
-        Ok(response)
+        self.poll_interval(interval)
     }
 
-    /// Sends a message to Twitch chat, if the message is over the 500 character limit
-    /// the message will be chunked into multiple parts and sent separately
-    pub async fn send_chat_message_chunked(&self, message: &str) -> anyhow::Result<()> {
-        if message.len() < 500 {
-            self.send_chat_message(message).await?;
-        } else {
-            let mut chars = message.chars();
-            loop {
-                let message = chars.by_ref().take(500).collect::<String>();
-                if message.is_empty() {
-                    break;
+    /// Interval the view count is updated at, configured through the plugin
+    /// properties and clamped to [POLL_INTERVAL_RANGE]
+    fn view_count_interval(&self) -> Duration {
+        let secs = self
+            .properties
+            .borrow()
+            .poll_interval_secs
+            .unwrap_or(DEFAULT_POLL_INTERVAL_SECS)
+            .clamp(*POLL_INTERVAL_RANGE.start(), *POLL_INTERVAL_RANGE.end());
+        Duration::from_secs(secs)
+    }
+
+    /// Fetches the latest value of the metric from twitch
+    async fn update_metric(&self, metric: Metric) {
+        match metric {
+            Metric::ViewCount => {
+                let started = Instant::now();
+                let result = self.get_view_count().await;
+
+                if result.is_ok() {
+                    self.api_latency
+                        .set(Some((started.elapsed(), Instant::now())));
                 }
 
-                self.send_chat_message(&message).await?;
+                match result {
+                    Ok(Some(view_count)) => {
+                        self.stream_offline.set(false);
+                        let previous = self.viewers.replace(view_count);
+
+                        // No change is reported without a previous online sample
+                        let delta = match self.viewers_delta.get() {
+                            Some(_) => view_count as i64 - previous as i64,
+                            None => 0,
+                        };
+                        self.viewers_delta.set(Some(delta));
+                    }
+                    // Stream is offline
+                    Ok(None) => {
+                        self.stream_offline.set(true);
+                        self.viewers_delta.set(None);
+                    }
+                    Err(error) => {
+                        tracing::error!(?error, "failed to get view count");
+                    }
+                }
             }
+            Metric::GoalProgress => match self.get_goal_progress().await {
+                Ok(progress) => {
+                    self.goal_progress.replace(progress);
+                }
+                Err(error) => {
+                    tracing::error!(?error, "failed to get goal progress");
+                }
+            },
+            Metric::ClipsToday => match self.get_clips_today().await {
+                Ok(count) => self.clips_today.set(count),
+                Err(error) => {
+                    tracing::error!(?error, "failed to get clips today");
+                }
+            },
+            Metric::PendingRedemptions => match self.get_pending_redemptions().await {
+                Ok(count) => self.pending_redemptions.set(count),
+                Err(error) => {
+                    tracing::error!(?error, "failed to get pending redemptions");
+                }
+            },
+            Metric::ActiveInteractions => match self.get_active_interactions().await {
+                Ok(counts) => self.active_interactions.set(counts),
+                Err(error) => {
+                    tracing::error!(?error, "failed to get active interactions");
+                }
+            },
+            Metric::CategoryViewers => match self.get_category_viewers().await {
+                Ok(value) => self.category_viewers.set(value),
+                Err(error) => {
+                    tracing::error!(?error, "failed to get category viewers");
+                }
+            },
+            Metric::FollowerCount => match self.get_follower_count().await {
+                Ok(count) => self.follower_count.set(Some(count)),
+                Err(error) => {
+                    tracing::error!(?error, "failed to get follower count");
+                }
+            },
+            Metric::Uptime => match self.get_stream_started_at().await {
+                Ok(started_at) => self.stream_started_at.set(started_at),
+                Err(error) => {
+                    tracing::error!(?error, "failed to get stream start time");
+                }
+            },
+            Metric::ChannelInfo => match self.get_channel_information().await {
+                Ok(channel) => {
+                    self.channel_info.replace(Some(channel));
+                }
+                Err(error) => {
+                    tracing::error!(?error, "failed to get channel information");
+                }
+            },
         }
-
-        Ok(())
     }
 
-    pub async fn clear_chat(&self) -> anyhow::Result<DeleteChatMessagesResponse> {
-        let token = self.get_user_token().context("not authenticated")?;
-        let user_id = token.user_id.clone();
-        let request = DeleteChatMessagesRequest::new(user_id.clone(), user_id);
-        let response: DeleteChatMessagesResponse =
-            self.helix_client.req_delete(request, &token).await?.data;
+    /// Round trip time of the most recent view count poll in milliseconds,
+    /// [None] when there is no sample from the last [API_LATENCY_MAX_AGE]
+    pub fn current_api_latency(&self) -> Option<u64> {
+        let (latency, measured_at) = self.api_latency.get()?;
+        if measured_at.elapsed() > API_LATENCY_MAX_AGE {
+            return None;
+        }
 
-        Ok(response)
+        Some(latency.as_millis() as u64)
     }
 
-    pub async fn create_clip(&self) -> anyhow::Result<Vec<CreatedClip>> {
-        let token = self.get_user_token().context("not authenticated")?;
+    /// Gets the progress of the first active creator goal, [None] when
+    /// there is no active goal
+    pub async fn get_goal_progress(&self) -> anyhow::Result<Option<GoalProgress>> {
+        let token = match self.get_user_token() {
+            Some(value) => value,
+            None => return Ok(None),
+        };
         let user_id = token.user_id.clone();
-        let request = CreateClipRequestFixed(CreateClipRequest::broadcaster_id(user_id));
-        let response: Vec<CreatedClip> = self
-            .helix_client
-            .req_post(request, EmptyBody, &token)
-            .await?
-            .data;
+        let request = GetCreatorGoalsRequest::broadcaster_id(user_id);
+        let response = self.helix_client.req_get(request, &token).await?.data;
 
-        Ok(response)
+        let progress = response.first().map(|goal| {
+            GoalProgress::new(goal.current_amount, goal.target_amount, &goal.goal_type)
+        });
+
+        Ok(progress)
     }
 
-    pub async fn create_marker(&self, description: String) -> anyhow::Result<CreatedStreamMarker> {
-        let token = self.get_user_token().context("not authenticated")?;
-        let user_id = token.user_id.clone();
-        let request = CreateStreamMarkerRequest::new();
-        let body = CreateStreamMarkerBody::new(user_id, description);
-        let response: CreatedStreamMarker = self
-            .helix_client
-            .req_post(request, body, &token)
-            .await?
-            .data;
+    /// Announces the progress of the active creator goal using the template,
+    /// returns false when there is no active goal
+    pub async fn announce_goal_progress(&self, template: &str) -> anyhow::Result<bool> {
+        let progress = match self.get_goal_progress().await? {
+            Some(value) => value,
+            None => return Ok(false),
+        };
 
-        Ok(response)
-    }
+        let mut values = self.template_values();
+        values.push(("percent", progress.percent.to_string()));
+        values.push(("current", format_count(progress.current)));
+        values.push(("target", format_count(progress.target)));
+        values.push(("unit", progress.unit.to_string()));
 
-    pub async fn start_comercial(
-        &self,
-        length: CommercialLength,
-    ) -> anyhow::Result<Vec<StartCommercial>> {
-        let token = self.get_user_token().context("not authenticated")?;
-        let user_id = token.user_id.clone();
-        let request = StartCommercialRequest::new();
-        let body = StartCommercialBody::new(user_id, length);
-        let response = self
-            .helix_client
-            .req_post(request, body, &token)
-            .await?
-            .data;
+        let message = expand_template(template, &values);
+        self.send_announcement(&message, AnnouncementColor::Primary)
+            .await?;
 
-        Ok(response)
+        self.goal_progress.replace(Some(progress));
+        Ok(true)
     }
 
-    pub async fn get_chat_settings(&self) -> anyhow::Result<ChatSettings> {
-        let token = self.get_user_token().context("not authenticated")?;
-        let user_id = token.user_id.clone();
-        let request = GetChatSettingsRequest::broadcaster_id(user_id.clone());
-        let response: ChatSettings = self.helix_client.req_get(request, &token).await?.data;
-        Ok(response)
+    pub fn current_goal_progress(&self) -> Option<GoalProgress> {
+        self.goal_progress.borrow().clone()
     }
 
-    pub async fn toggle_slow_mode(&self) -> anyhow::Result<()> {
-        let settings = self.get_chat_settings().await?;
+    /// Counts the clips of the users channel created since midnight (UTC)
+    ///
+    /// Only the first [MAX_CLIP_COUNT_PAGES] pages of clips are counted
+    pub async fn get_clips_today(&self) -> anyhow::Result<usize> {
         let token = self.get_user_token().context("not authenticated")?;
         let user_id = token.user_id.clone();
-        let request = UpdateChatSettingsRequest::new(user_id.clone(), user_id);
-        let mut body = UpdateChatSettingsBody::default();
-        body.slow_mode = Some(!settings.slow_mode);
+        let started_at = Timestamp::new(format_date(&Timestamp::now(), "YYYY-MM-DDT00:00:00Z"))
+            .context("invalid start of day timestamp")?;
 
-        _ = self.helix_client.req_patch(request, body, &token).await?;
-        Ok(())
+        let request = GetClipsRequest::broadcaster_id(user_id)
+            .started_at(&started_at)
+            .first(100);
+        let mut response = self.helix_client.req_get(request, &token).await?;
+        let mut count = 0;
+
+        for _ in 0..MAX_CLIP_COUNT_PAGES {
+            count += response.data.len();
+            response = match response.get_next(&self.helix_client, &token).await? {
+                Some(value) => value,
+                None => break,
+            };
+        }
+
+        Ok(count)
     }
 
-    pub async fn toggle_emote_only(&self) -> anyhow::Result<()> {
-        let settings = self.get_chat_settings().await?;
+    /// Posts a random clip from the last [RANDOM_CLIP_WINDOW_DAYS] days to chat,
+    /// returns false when there are no clips to pick from
+    pub async fn post_random_clip(&self, template: &str) -> anyhow::Result<bool> {
         let token = self.get_user_token().context("not authenticated")?;
         let user_id = token.user_id.clone();
-        let request = UpdateChatSettingsRequest::new(user_id.clone(), user_id);
-        let mut body = UpdateChatSettingsBody::default();
-        body.emote_mode = Some(!settings.emote_mode);
 
-        _ = self.helix_client.req_patch(request, body, &token).await?;
-        Ok(())
+        let now = timestamp_seconds(&Timestamp::now()).context("invalid current timestamp")?;
+        let started_at = Timestamp::new(format_timestamp(now - RANDOM_CLIP_WINDOW_DAYS * 86400))
+            .context("invalid clip window timestamp")?;
+
+        let request = GetClipsRequest::broadcaster_id(user_id)
+            .started_at(&started_at)
+            .first(RANDOM_CLIP_POOL_SIZE);
+        let clips: Vec<_> = self.helix_client.req_get(request, &token).await?.data;
+
+        if clips.is_empty() {
+            return Ok(false);
+        }
+
+        let clip = &clips[random_index(clips.len())];
+
+        let mut values = self.template_values();
+        values.push(("url", clip.url.clone()));
+        values.push(("title", clip.title.clone()));
+        values.push(("creator", clip.creator_name.to_string()));
+
+        let message = expand_template(template, &values);
+        self.send_chat_message(&message).await?;
+        Ok(true)
     }
 
-    pub async fn toggle_follower_only(&self) -> anyhow::Result<()> {
-        let settings = self.get_chat_settings().await?;
-        let token = self.get_user_token().context("not authenticated")?;
-        let user_id = token.user_id.clone();
-        let request = UpdateChatSettingsRequest::new(user_id.clone(), user_id);
-        let mut body = UpdateChatSettingsBody::default();
-        body.follower_mode = Some(!settings.follower_mode);
+    pub fn current_clips_today(&self) -> usize {
+        self.clips_today.get()
+    }
+}
 
-        _ = self.helix_client.req_patch(request, body, &token).await?;
-        Ok(())
+/// Picks a random index below `len` using the randomly seeded std hasher
+fn random_index(len: usize) -> usize {
+    let value = RandomState::new().build_hasher().finish();
+    (value % len.max(1) as u64) as usize
+}
+
+/// Describes each of the active chat restrictions (e.g "30s slow mode")
+pub fn chat_restrictions(settings: &ChatSettings) -> Vec<String> {
+    let mut restrictions = Vec::new();
+
+    if settings.emote_mode {
+        restrictions.push("emote-only".to_string());
     }
 
-    pub async fn toggle_sub_only(&self) -> anyhow::Result<()> {
-        let settings = self.get_chat_settings().await?;
-        let token = self.get_user_token().context("not authenticated")?;
-        let user_id = token.user_id.clone();
-        let request = UpdateChatSettingsRequest::new(user_id.clone(), user_id);
-        let mut body = UpdateChatSettingsBody::default();
-        body.subscriber_mode = Some(!settings.subscriber_mode);
+    if settings.subscriber_mode {
+        restrictions.push("sub-only".to_string());
+    }
 
-        _ = self.helix_client.req_patch(request, body, &token).await?;
-        Ok(())
+    if settings.follower_mode {
+        restrictions.push(match settings.follower_mode_duration {
+            Some(minutes) if minutes > 0 => format!("followers-only ({minutes}m)"),
+            _ => "followers-only".to_string(),
+        });
     }
 
-    pub async fn get_view_count(&self) -> anyhow::Result<Option<usize>> {
-        let token = match self.get_user_token() {
-            Some(value) => value,
-            None => return Ok(None),
-        };
-        let user_id = token.user_id.clone();
-        let request = GetStreamsRequest::user_ids(vec![user_id]).first(1);
+    if settings.slow_mode {
+        restrictions.push(match settings.slow_mode_wait_time {
+            Some(seconds) => format!("{seconds}s slow mode"),
+            None => "slow mode".to_string(),
+        });
+    }
 
-        let response = self.helix_client.req_get(request, &token).await?.data;
-        let view_count = response.first().map(|stream| stream.viewer_count);
-        Ok(view_count)
+    if settings.unique_chat_mode {
+        restrictions.push("unique chat".to_string());
     }
 
-    // Returning the number of active ones
-    pub fn get_active_displays(&self) -> usize {
-        let now = Instant::now();
-        let displays = &mut *self.view_displays.borrow_mut();
-        displays.retain(|display| now.duration_since(display.last_alive) < Duration::from_secs(5));
+    restrictions
+}
 
-        displays.len()
+/// Fits the title within [MAX_TITLE_LENGTH] characters according to the
+/// overflow behavior, provides the title length as the error when rejected
+pub fn fit_title(title: &str, overflow: TitleOverflow) -> Result<Cow<'_, str>, usize> {
+    let length = title.chars().count();
+    if length <= MAX_TITLE_LENGTH {
+        return Ok(Cow::Borrowed(title));
     }
 
-    pub fn current_view_count(&self) -> usize {
-        self.viewers.get()
+    match overflow {
+        TitleOverflow::Reject => Err(length),
+        TitleOverflow::Truncate => Ok(Cow::Owned(title.chars().take(MAX_TITLE_LENGTH).collect())),
     }
+}
+
+/// Raid received by the authenticated users channel
+#[derive(Debug, Clone)]
+pub struct IncomingRaid {
+    /// Login of the raiding broadcaster
+    pub from_login: String,
+    /// Number of viewers brought by the raid
+    pub viewers: i64,
+    /// Time the raid was received
+    pub received_at: Instant,
+}
 
-    pub fn push_active_display(&self, display: &Display) {
-        let displays = &mut *self.view_displays.borrow_mut();
-        let now = Instant::now();
+/// Slow mode settings captured before being temporarily replaced
+#[derive(Debug, Clone, Copy)]
+pub struct SlowModeSettings {
+    pub enabled: bool,
+    pub wait_time: Option<u64>,
+}
 
-        if let Some(existing) = displays
-            .iter_mut()
-            .find(|other| other.display.ctx.eq(&display.ctx))
-        {
-            existing.last_alive = now;
+/// Progress towards a creator goal
+#[derive(Debug, Clone)]
+pub struct GoalProgress {
+    /// Percentage of the target reached
+    pub percent: u32,
+    /// Preformatted progress (e.g "84% — 420/500")
+    pub label: String,
+    /// Current amount towards the goal
+    pub current: i64,
+    /// Target amount for the goal
+    pub target: i64,
+    /// What the goal is counting (e.g "followers")
+    pub unit: &'static str,
+}
+
+impl GoalProgress {
+    pub fn new(current: i64, target: i64, goal_type: &CreatorGoalType) -> Self {
+        let percent = if target > 0 {
+            (current.max(0) * 100 / target) as u32
         } else {
-            displays.push(ViewCountDisplay {
-                display: display.clone(),
-                last_alive: now,
-            });
+            0
+        };
+
+        let label = format!(
+            "{percent}% — {}/{}",
+            format_count(current),
+            format_count(target)
+        );
+
+        let unit = match goal_type {
+            CreatorGoalType::Follower => "followers",
+            _ => "subs",
+        };
+
+        Self {
+            percent,
+            label,
+            current,
+            target,
+            unit,
         }
     }
 }
@@ -335,30 +3357,140 @@ impl RequestPost for CreateClipRequestFixed<'_> {
     type Body = EmptyBody;
 }
 
-pub struct ViewCountDisplay {
-    display: Display,
-    last_alive: Instant,
+/// Creates the inspector message describing the authenticated user
+fn user_info_message(user: &User) -> InspectorMessageOut {
+    InspectorMessageOut::UserInfo {
+        login: user.login.to_string(),
+        display_name: user.display_name.to_string(),
+        profile_image_url: user.profile_image_url.clone().unwrap_or_default(),
+    }
 }
 
-pub async fn run_view_count_update(state: Rc<State>) {
+/// Background task refreshing the metrics that have active displays, every
+/// metric shares a single tick and is only fetched once its interval elapsed
+pub async fn run_metrics_update(state: Rc<State>) {
+    let mut last_updated: HashMap<Metric, Instant> = HashMap::new();
+
     loop {
-        let active = state.get_active_displays();
+        // Counting also prunes displays that are no longer active, while idle
+        // the metric intervals are lengthened rather than skipping fetches
+        if state.displays.active_count() > 0 {
+            for metric in Metric::ALL {
+                if !state.displays.is_active(metric) {
+                    continue;
+                }
 
-        if active > 0 {
-            let view_count = match state.get_view_count().await {
-                Ok(value) => value,
-                Err(error) => {
-                    tracing::error!(?error, "failed to get view count");
-                    None
+                let interval = state.metric_interval(metric);
+                if last_updated
+                    .get(&metric)
+                    .is_some_and(|updated| updated.elapsed() < interval)
+                {
+                    continue;
                 }
-            };
 
-            if let Some(view_count) = view_count {
-                state.viewers.replace(view_count);
+                last_updated.insert(metric, Instant::now());
+                state.update_metric(metric).await;
+            }
+        }
+
+        sleep(METRICS_TICK).await;
+    }
+}
+
+/// Background task periodically checking the access tokens are still valid,
+/// logging out and forgetting tokens that twitch no longer accepts
+pub async fn run_token_validation(state: Rc<State>, session: PluginSessionHandle) {
+    loop {
+        sleep(TOKEN_VALIDATION_INTERVAL).await;
+
+        if let Some(token) = state.get_user_token() {
+            match state.check_token(&token).await {
+                // Keeps the token expiry shown in the inspector current
+                Ok(_) => state.update_inspector(),
+                Err(error) if error.is_invalid_token() => {
+                    // Token may have been replaced by a new login while validating
+                    if state
+                        .get_user_token()
+                        .is_some_and(|current| current.access_token == token.access_token)
+                    {
+                        tracing::warn!(?error, "access token is no longer valid, logging out");
+                        state.set_logged_out();
+                        state.update_properties(&session, |properties| properties.access = None);
+                    }
+                }
+                Err(error) => tracing::warn!(?error, "failed to validate access token"),
+            }
+        }
+
+        if let Some(token) = state.get_bot_token() {
+            match state.check_token(&token).await {
+                Ok(_) => {}
+                Err(error) if error.is_invalid_token() => {
+                    if state
+                        .get_bot_token()
+                        .is_some_and(|current| current.access_token == token.access_token)
+                    {
+                        tracing::warn!(?error, "bot access token is no longer valid, logging out");
+                        state.set_bot_logged_out();
+                        state
+                            .update_properties(&session, |properties| properties.bot_access = None);
+                    }
+                }
+                Err(error) => tracing::warn!(?error, "failed to validate bot access token"),
             }
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Titles at the limit are used as is
+    #[test]
+    fn fit_title_at_limit() {
+        let title = "a".repeat(MAX_TITLE_LENGTH);
+        assert_eq!(
+            fit_title(&title, TitleOverflow::Reject),
+            Ok(Cow::Borrowed(&*title))
+        );
+        assert_eq!(
+            fit_title(&title, TitleOverflow::Truncate),
+            Ok(Cow::Borrowed(&*title))
+        );
+    }
+
+    /// Titles over the limit are rejected with their length
+    #[test]
+    fn fit_title_over_limit_rejected() {
+        let title = "a".repeat(MAX_TITLE_LENGTH + 1);
+        assert_eq!(fit_title(&title, TitleOverflow::Reject), Err(141));
+    }
+
+    /// Titles over the limit are truncated to the limit
+    #[test]
+    fn fit_title_over_limit_truncated() {
+        let title = "a".repeat(MAX_TITLE_LENGTH + 1);
+        let expected = "a".repeat(MAX_TITLE_LENGTH);
+        assert_eq!(
+            fit_title(&title, TitleOverflow::Truncate),
+            Ok(Cow::Owned(expected))
+        );
+    }
+
+    /// Multibyte titles are measured in characters rather than bytes
+    #[test]
+    fn fit_title_multibyte() {
+        let title = "🎮".repeat(MAX_TITLE_LENGTH);
+        assert_eq!(
+            fit_title(&title, TitleOverflow::Reject),
+            Ok(Cow::Borrowed(&*title))
+        );
+
+        let title = "🎮".repeat(MAX_TITLE_LENGTH + 1);
+        assert_eq!(fit_title(&title, TitleOverflow::Reject), Err(141));
 
-        // Update every 5 seconds
-        sleep(Duration::from_secs(5)).await;
+        let truncated = fit_title(&title, TitleOverflow::Truncate).unwrap();
+        assert_eq!(truncated, "🎮".repeat(MAX_TITLE_LENGTH));
     }
 }
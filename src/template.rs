@@ -0,0 +1,35 @@
+/// Expands `{name}` placeholders within the template using the provided
+/// values, placeholders without a matching value are left unchanged
+pub fn expand_template(template: &str, values: &[(&str, String)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+
+        let after = &rest[start + 1..];
+        let end = match after.find('}') {
+            Some(value) => value,
+            // Unclosed placeholder
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        };
+
+        let name = &after[..end];
+        match values.iter().find(|(key, _)| *key == name) {
+            Some((_, value)) => output.push_str(value),
+            None => {
+                output.push('{');
+                output.push_str(name);
+                output.push('}');
+            }
+        }
+
+        rest = &after[end + 1..];
+    }
+
+    output.push_str(rest);
+    output
+}